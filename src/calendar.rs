@@ -0,0 +1,400 @@
+use crate::clock::{Clock, SystemClock};
+use crate::period::PeriodError;
+use chrono::{DateTime, Datelike, Days, LocalResult, Months, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::sync::Arc;
+
+/// The calendar unit a CalendarPeriod steps in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarUnit {
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+/// What happens to an occurrence whose local time is skipped, e.g. 02:30 when the clocks are set
+/// forward from 02:00 to 03:00.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonexistentTimePolicy {
+    /// Shift the occurrence forward by the length of the gap, e.g. from 02:30 to 03:30.
+    #[default]
+    ShiftForward,
+    /// Drop the occurrence.
+    Skip,
+}
+
+/// What happens to an occurrence whose local time occurs twice, e.g. 02:30 when the clocks are set
+/// back from 03:00 to 02:00.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmbiguousTimePolicy {
+    /// Use the first of both times.
+    #[default]
+    Earliest,
+    /// Use the second of both times.
+    Latest,
+    /// Fire at both times.
+    Both,
+}
+
+/// A period which steps in local calendar units in a time zone, so every occurrence keeps the local
+/// time of day of the start across changes of the UTC offset.
+///
+/// Occurrences are counted from the start. When stepping in months or years, a day which does not
+/// exist in the target month is clamped to the last day of that month, e.g. the 31st of January is
+/// followed by the 29th of February 2020 and the 31st of March.
+#[derive(Clone)]
+pub struct CalendarPeriod {
+    start: NaiveDateTime,
+    timezone: Tz,
+    interval: u32,
+    unit: CalendarUnit,
+    nonexistent_time_policy: NonexistentTimePolicy,
+    ambiguous_time_policy: AmbiguousTimePolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl CalendarPeriod {
+    /// Create a CalendarPeriod which repeats every `interval` `unit`s at the local time of `start`.
+    /// Fails if the interval is zero.
+    pub fn starting_at(
+        start: DateTime<Tz>,
+        interval: u32,
+        unit: CalendarUnit,
+    ) -> Result<Self, PeriodError> {
+        if interval == 0 {
+            return Err(PeriodError::ZeroDurationError);
+        }
+
+        Ok(CalendarPeriod {
+            start: start.naive_local(),
+            timezone: start.timezone(),
+            interval,
+            unit,
+            nonexistent_time_policy: NonexistentTimePolicy::default(),
+            ambiguous_time_policy: AmbiguousTimePolicy::default(),
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    pub fn with_nonexistent_time_policy(mut self, policy: NonexistentTimePolicy) -> Self {
+        self.nonexistent_time_policy = policy;
+        self
+    }
+
+    pub fn with_ambiguous_time_policy(mut self, policy: AmbiguousTimePolicy) -> Self {
+        self.ambiguous_time_policy = policy;
+        self
+    }
+
+    /// Use the clock to decide which timestamps are upcoming. Defaults to the system clock.
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Return an iterator of DateTimes that takes ownership of the CalendarPeriod. The iterator can generate values in the past.
+    pub fn upcoming_fixed_owned(self) -> CalendarPeriodIterator {
+        CalendarPeriodIterator::new(self, None)
+    }
+
+    /// Return an iterator of DateTimes that takes ownership of the CalendarPeriod. That iterator will only generate values in the future.
+    pub fn upcoming_relative_owned(self) -> CalendarPeriodIterator {
+        let now = self.clock.now();
+        self.upcoming_after_owned(now)
+    }
+
+    /// Return an iterator of DateTimes that takes ownership of the CalendarPeriod. That iterator will only generate values after `after`.
+    pub fn upcoming_after_owned(self, after: DateTime<Utc>) -> CalendarPeriodIterator {
+        CalendarPeriodIterator::new(self, Some(after))
+    }
+
+    /// The local date and time of the occurrence with the given index. `None` if it can not be represented.
+    fn local_occurrence(&self, index: u32) -> Option<NaiveDateTime> {
+        let steps = index.checked_mul(self.interval)?;
+        match self.unit {
+            CalendarUnit::Days => self.start.checked_add_days(Days::new(u64::from(steps))),
+            CalendarUnit::Weeks => self.start.checked_add_days(Days::new(u64::from(steps) * 7)),
+            CalendarUnit::Months => self.start.checked_add_months(Months::new(steps)),
+            CalendarUnit::Years => self
+                .start
+                .checked_add_months(Months::new(steps.checked_mul(12)?)),
+        }
+    }
+
+    /// An index whose occurrence lies at or before `after`, so the occurrences after `after` do not
+    /// have to be searched from the start.
+    fn index_before(&self, after: DateTime<Utc>) -> u32 {
+        let after = after.with_timezone(&self.timezone).naive_local();
+        if after <= self.start {
+            return 0;
+        }
+
+        let elapsed_units = match self.unit {
+            CalendarUnit::Days => (after.date() - self.start.date()).num_days(),
+            CalendarUnit::Weeks => (after.date() - self.start.date()).num_days() / 7,
+            CalendarUnit::Months => {
+                i64::from(after.year() - self.start.year()) * 12 + i64::from(after.month())
+                    - i64::from(self.start.month())
+            }
+            CalendarUnit::Years => i64::from(after.year() - self.start.year()),
+        };
+        // the estimate can be one step too far, e.g. for an earlier time of day
+        let index = (elapsed_units / i64::from(self.interval)).saturating_sub(1);
+        u32::try_from(index.max(0)).unwrap_or(u32::MAX)
+    }
+
+    /// The UTC times of a local time according to the policies.
+    fn resolve(&self, local: NaiveDateTime) -> Vec<DateTime<Utc>> {
+        match self.timezone.from_local_datetime(&local) {
+            LocalResult::Single(time) => vec![time.to_utc()],
+            LocalResult::Ambiguous(earliest, latest) => match self.ambiguous_time_policy {
+                AmbiguousTimePolicy::Earliest => vec![earliest.to_utc()],
+                AmbiguousTimePolicy::Latest => vec![latest.to_utc()],
+                AmbiguousTimePolicy::Both => vec![earliest.to_utc(), latest.to_utc()],
+            },
+            LocalResult::None => match self.nonexistent_time_policy {
+                NonexistentTimePolicy::ShiftForward => {
+                    shift_forward(&self.timezone, local).into_iter().collect()
+                }
+                NonexistentTimePolicy::Skip => Vec::new(),
+            },
+        }
+    }
+}
+
+/// Interpret a local time which lies in a gap with the UTC offset from before the gap, which moves
+/// it forward by the length of the gap.
+fn shift_forward(timezone: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    let before_gap = timezone
+        .from_local_datetime(&local.checked_sub_days(Days::new(1))?)
+        .earliest()?;
+    let offset = before_gap.offset().fix();
+    Some(offset.from_local_datetime(&local).single()?.to_utc())
+}
+
+pub struct CalendarPeriodIterator {
+    period: CalendarPeriod,
+    index: Option<u32>,
+    after: Option<DateTime<Utc>>,
+    pending: Vec<DateTime<Utc>>,
+}
+
+impl CalendarPeriodIterator {
+    fn new(period: CalendarPeriod, after: Option<DateTime<Utc>>) -> Self {
+        let index = after.map_or(0, |after| period.index_before(after));
+        CalendarPeriodIterator {
+            period,
+            index: Some(index),
+            after,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Iterator for CalendarPeriodIterator {
+    type Item = DateTime<Utc>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.pending.is_empty() {
+                let index = self.index?;
+                self.index = index.checked_add(1);
+                let Some(local) = self.period.local_occurrence(index) else {
+                    self.index = None;
+                    return None;
+                };
+                self.pending = self.period.resolve(local);
+                // both times of an ambiguous occurrence are taken from the front
+                self.pending.reverse();
+            }
+            match self.pending.pop() {
+                Some(time) if self.after.is_some_and(|after| time <= after) => continue,
+                Some(time) => return Some(time),
+                None => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+    use chrono_tz::Europe::Berlin;
+
+    use crate::clock::MockClock;
+
+    use super::*;
+
+    fn berlin(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Tz> {
+        Berlin
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn that_calendar_period_can_not_be_created_with_zero_interval() {
+        let result = CalendarPeriod::starting_at(berlin(2020, 1, 1, 7, 0), 0, CalendarUnit::Days);
+
+        assert!(matches!(result, Err(PeriodError::ZeroDurationError)));
+    }
+
+    #[test]
+    fn that_daily_period_keeps_local_time_across_start_of_daylight_saving_time() {
+        let period =
+            CalendarPeriod::starting_at(berlin(2020, 3, 28, 7, 0), 1, CalendarUnit::Days).unwrap();
+
+        let result: Vec<_> = period.upcoming_fixed_owned().take(2).collect();
+
+        assert_eq!(vec![utc(2020, 3, 28, 6, 0), utc(2020, 3, 29, 5, 0)], result);
+    }
+
+    #[test]
+    fn that_weekly_period_keeps_local_time_across_end_of_daylight_saving_time() {
+        let period =
+            CalendarPeriod::starting_at(berlin(2020, 10, 20, 7, 0), 2, CalendarUnit::Weeks)
+                .unwrap();
+
+        let result: Vec<_> = period.upcoming_fixed_owned().take(2).collect();
+
+        assert_eq!(
+            vec![utc(2020, 10, 20, 5, 0), utc(2020, 11, 3, 6, 0)],
+            result
+        );
+    }
+
+    #[test]
+    fn that_monthly_period_clamps_to_last_day_of_month() {
+        let period =
+            CalendarPeriod::starting_at(berlin(2020, 1, 31, 12, 0), 1, CalendarUnit::Months)
+                .unwrap();
+
+        let result: Vec<_> = period.upcoming_fixed_owned().take(3).collect();
+
+        assert_eq!(
+            vec![
+                utc(2020, 1, 31, 11, 0),
+                utc(2020, 2, 29, 11, 0),
+                utc(2020, 3, 31, 10, 0),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn that_yearly_period_starting_on_leap_day_is_clamped_in_other_years() {
+        let period =
+            CalendarPeriod::starting_at(berlin(2020, 2, 29, 12, 0), 1, CalendarUnit::Years)
+                .unwrap();
+
+        let result: Vec<_> = period.upcoming_fixed_owned().take(5).collect();
+
+        assert_eq!(utc(2021, 2, 28, 11, 0), result[1]);
+        assert_eq!(utc(2024, 2, 29, 11, 0), result[4]);
+    }
+
+    #[test]
+    fn that_nonexistent_local_time_is_shifted_forward_by_default() {
+        let period =
+            CalendarPeriod::starting_at(berlin(2020, 3, 28, 2, 30), 1, CalendarUnit::Days).unwrap();
+
+        let result: Vec<_> = period.upcoming_fixed_owned().take(3).collect();
+
+        // 02:30 does not exist on the 29th, so the occurrence is at 03:30 summer time
+        assert_eq!(
+            vec![
+                utc(2020, 3, 28, 1, 30),
+                utc(2020, 3, 29, 1, 30),
+                utc(2020, 3, 30, 0, 30),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn that_nonexistent_local_time_can_be_skipped() {
+        let period = CalendarPeriod::starting_at(berlin(2020, 3, 28, 2, 30), 1, CalendarUnit::Days)
+            .unwrap()
+            .with_nonexistent_time_policy(NonexistentTimePolicy::Skip);
+
+        let result: Vec<_> = period.upcoming_fixed_owned().take(2).collect();
+
+        assert_eq!(
+            vec![utc(2020, 3, 28, 1, 30), utc(2020, 3, 30, 0, 30)],
+            result
+        );
+    }
+
+    #[test]
+    fn that_ambiguous_local_time_is_resolved_according_to_policy() {
+        let start = berlin(2020, 10, 24, 2, 30);
+        let occurrence_on_25th = |policy| {
+            CalendarPeriod::starting_at(start, 1, CalendarUnit::Days)
+                .unwrap()
+                .with_ambiguous_time_policy(policy)
+                .upcoming_fixed_owned()
+                .skip(1)
+                .take_while(|time| *time < utc(2020, 10, 26, 0, 0))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec![utc(2020, 10, 25, 0, 30)],
+            occurrence_on_25th(AmbiguousTimePolicy::Earliest)
+        );
+        assert_eq!(
+            vec![utc(2020, 10, 25, 1, 30)],
+            occurrence_on_25th(AmbiguousTimePolicy::Latest)
+        );
+        assert_eq!(
+            vec![utc(2020, 10, 25, 0, 30), utc(2020, 10, 25, 1, 30)],
+            occurrence_on_25th(AmbiguousTimePolicy::Both)
+        );
+    }
+
+    #[test]
+    fn that_relative_iterator_starts_after_the_clock() {
+        let clock = MockClock::new(utc(2021, 6, 15, 5, 0));
+        let period = CalendarPeriod::starting_at(berlin(2020, 1, 1, 7, 0), 1, CalendarUnit::Days)
+            .unwrap()
+            .with_clock(clock);
+
+        let next = period.upcoming_relative_owned().next().unwrap();
+
+        assert_eq!(utc(2021, 6, 15, 5, 0) + Duration::days(1), next);
+    }
+
+    #[test]
+    fn that_iterator_after_time_skips_the_occurrence_at_that_time() {
+        let start = berlin(2020, 1, 15, 7, 0);
+        let period = CalendarPeriod::starting_at(start, 3, CalendarUnit::Months).unwrap();
+
+        let next = period
+            .upcoming_after_owned(utc(2020, 7, 15, 5, 0))
+            .next()
+            .unwrap();
+
+        assert_eq!(utc(2020, 10, 15, 5, 0), next);
+    }
+
+    #[test]
+    fn that_local_time_of_start_is_kept_with_seconds() {
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1)
+            .unwrap()
+            .and_hms_opt(7, 0, 30)
+            .unwrap()
+            .and_local_timezone(Berlin)
+            .unwrap();
+        let period = CalendarPeriod::starting_at(start, 1, CalendarUnit::Years).unwrap();
+
+        let next = period.upcoming_fixed_owned().nth(1).unwrap();
+
+        assert_eq!(utc(2021, 1, 1, 6, 0) + Duration::seconds(30), next);
+    }
+}
@@ -0,0 +1,120 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::watch;
+
+/// Source of the current time for periods and the Zeitschaltuhr.
+pub trait Clock: Send + Sync + 'static {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Wait until `duration` has passed on this clock.
+    fn sleep(&self, duration: std::time::Duration)
+        -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+}
+
+/// The system time. Sleeping is done by the tokio timer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(
+        &self,
+        duration: std::time::Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock which only moves when it is advanced by hand. Clones share the same time, so one clone
+/// can be handed to a Zeitschaltuhr while the test keeps another one to advance it.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<watch::Sender<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        let (now, _) = watch::channel(now);
+        Self { now: Arc::new(now) }
+    }
+
+    /// Move the clock forward and wake up everything sleeping until then.
+    pub fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| *now += duration);
+    }
+
+    /// Set the clock to an arbitrary time. Setting it into the past simulates a corrected system time.
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now.send_replace(now);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    fn sleep(
+        &self,
+        duration: std::time::Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let target = Duration::from_std(duration)
+            .ok()
+            .and_then(|duration| self.now().checked_add_signed(duration))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let mut receiver = self.now.subscribe();
+        Box::pin(async move {
+            let _ = receiver.wait_for(|now| *now >= target).await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn that_mock_clock_only_moves_when_advanced() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+
+        assert_eq!(clock.now(), start);
+        clock.clone().advance(Duration::minutes(5));
+
+        assert_eq!(clock.now(), start + Duration::minutes(5));
+    }
+
+    #[tokio::test]
+    async fn that_mock_clock_wakes_sleepers_once_advanced_far_enough() {
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
+        let woken = Arc::new(AtomicBool::new(false));
+        let sleeper = {
+            let clock = clock.clone();
+            let woken = woken.clone();
+            tokio::spawn(async move {
+                clock.sleep(std::time::Duration::from_secs(60)).await;
+                woken.store(true, Ordering::SeqCst);
+            })
+        };
+        tokio::task::yield_now().await;
+
+        clock.advance(Duration::seconds(59));
+        tokio::task::yield_now().await;
+        assert!(!woken.load(Ordering::SeqCst));
+
+        clock.advance(Duration::seconds(1));
+        sleeper.await.unwrap();
+        assert!(woken.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+
+use crate::misfire::MisfireReason;
+use crate::zeitschaltuhr::TaskId;
+
+/// Something that happened while the Zeitschaltuhr was running a task.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A task was registered and is scheduled from now on.
+    TaskAdded {
+        task_id: TaskId,
+        /// The name of the task if it was given one.
+        name: Option<String>,
+        added_at: DateTime<Utc>,
+    },
+    /// A task was removed and is not scheduled anymore.
+    TaskRemoved {
+        task_id: TaskId,
+        removed_at: DateTime<Utc>,
+    },
+    /// An occurrence became due.
+    TriggerFired {
+        task_id: TaskId,
+        scheduled_at: DateTime<Utc>,
+        fired_at: DateTime<Utc>,
+    },
+    ExecutionStarted {
+        task_id: TaskId,
+        scheduled_at: DateTime<Utc>,
+        started_at: DateTime<Utc>,
+        attempt: u32,
+    },
+    /// An execution finished successfully.
+    ExecutionFinished {
+        task_id: TaskId,
+        scheduled_at: DateTime<Utc>,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        /// The duration measured in real time, independent of the clock of the Zeitschaltuhr.
+        duration: std::time::Duration,
+        attempt: u32,
+    },
+    /// An execution returned an error or exceeded its timeout.
+    ExecutionFailed {
+        task_id: TaskId,
+        scheduled_at: DateTime<Utc>,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        /// The duration measured in real time, independent of the clock of the Zeitschaltuhr.
+        duration: std::time::Duration,
+        attempt: u32,
+        error: String,
+    },
+    /// An occurrence was not executed.
+    OccurrenceSkipped {
+        task_id: TaskId,
+        scheduled_at: DateTime<Utc>,
+        skipped_at: DateTime<Utc>,
+        reason: SkipReason,
+    },
+    /// The temporal iterator of the task yields no more occurrences and the last execution has finished.
+    ScheduleExhausted {
+        task_id: TaskId,
+        exhausted_at: DateTime<Utc>,
+    },
+}
+
+impl Event {
+    pub fn task_id(&self) -> TaskId {
+        match self {
+            Event::TaskAdded { task_id, .. }
+            | Event::TaskRemoved { task_id, .. }
+            | Event::TriggerFired { task_id, .. }
+            | Event::ExecutionStarted { task_id, .. }
+            | Event::ExecutionFinished { task_id, .. }
+            | Event::ExecutionFailed { task_id, .. }
+            | Event::OccurrenceSkipped { task_id, .. }
+            | Event::ScheduleExhausted { task_id, .. } => *task_id,
+        }
+    }
+}
+
+/// Reason why an occurrence was not executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The task was paused.
+    Paused,
+    /// A previous execution was still running and the overlap policy did not allow another one.
+    Overlapping,
+    /// Another task of the exclusion group was running.
+    GroupBusy,
+    /// The occurrence was dropped by the misfire policy or the maximum lateness.
+    Misfire(MisfireReason),
+}
+
+/// Receives the events of all tasks of a Zeitschaltuhr. Listeners are called on the scheduler's
+/// threads and should return quickly.
+pub trait EventListener: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+impl<F> EventListener for F
+where
+    F: Fn(&Event) + Send + Sync,
+{
+    fn on_event(&self, event: &Event) {
+        self(event)
+    }
+}
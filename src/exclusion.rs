@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OwnedMutexGuard;
+
+/// Decides what happens when a task should be executed while another task of its exclusion group is running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExclusionBehavior {
+    /// Wait until the running task has finished.
+    #[default]
+    Wait,
+    /// Skip the execution.
+    Skip,
+}
+
+/// Tasks of the same exclusion group are never executed at the same time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExclusionGroup {
+    pub(crate) name: String,
+    pub(crate) behavior: ExclusionBehavior,
+}
+
+impl ExclusionGroup {
+    pub fn new(name: impl Into<String>, behavior: ExclusionBehavior) -> Self {
+        Self {
+            name: name.into(),
+            behavior,
+        }
+    }
+}
+
+/// Locks of all exclusion groups used by the tasks of a Zeitschaltuhr.
+#[derive(Default)]
+pub(crate) struct ExclusionGroups {
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl ExclusionGroups {
+    /// Enter the group. Returns None if the group is busy and the execution has to be skipped.
+    pub(crate) async fn enter(&self, group: &ExclusionGroup) -> Option<OwnedMutexGuard<()>> {
+        let lock = self
+            .locks
+            .lock()
+            .unwrap()
+            .entry(group.name.clone())
+            .or_default()
+            .clone();
+
+        match group.behavior {
+            ExclusionBehavior::Wait => Some(lock.lock_owned().await),
+            ExclusionBehavior::Skip => lock.try_lock_owned().ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[tokio::test]
+    async fn that_group_can_only_be_entered_once() {
+        let groups = ExclusionGroups::default();
+        let group = ExclusionGroup::new("database", ExclusionBehavior::Skip);
+
+        let guard = groups.enter(&group).await;
+        assert!(guard.is_some());
+        assert!(groups.enter(&group).await.is_none());
+
+        drop(guard);
+        assert!(groups.enter(&group).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn that_different_groups_do_not_exclude_each_other() {
+        let groups = ExclusionGroups::default();
+        let database = ExclusionGroup::new("database", ExclusionBehavior::Skip);
+        let network = ExclusionGroup::new("network", ExclusionBehavior::Skip);
+
+        let _guard = groups.enter(&database).await;
+
+        assert!(groups.enter(&network).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn that_waiting_member_enters_once_group_is_left() {
+        let groups = Arc::new(ExclusionGroups::default());
+        let group = ExclusionGroup::new("database", ExclusionBehavior::Wait);
+        let guard = groups.enter(&group).await;
+
+        let waiting_groups = groups.clone();
+        let waiting_group = group.clone();
+        let waiting =
+            tokio::spawn(async move { waiting_groups.enter(&waiting_group).await.is_some() });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(guard);
+        assert!(waiting.await.unwrap());
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+
+use crate::task::{TaskResult, TimeoutError};
+
+/// Outcome of a single execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    Succeeded,
+    /// The task returned an error, described by the message.
+    Failed(String),
+    /// The execution exceeded its timeout.
+    TimedOut,
+}
+
+impl From<&TaskResult> for ExecutionOutcome {
+    fn from(result: &TaskResult) -> Self {
+        match result {
+            Ok(()) => ExecutionOutcome::Succeeded,
+            Err(error) if error.is::<TimeoutError>() => ExecutionOutcome::TimedOut,
+            Err(error) => ExecutionOutcome::Failed(error.to_string()),
+        }
+    }
+}
+
+/// A past execution of a task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionRecord {
+    pub scheduled_at: DateTime<Utc>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration: std::time::Duration,
+    /// Number of the attempt, starting with 1 for the first execution of an occurrence.
+    pub attempt: u32,
+    pub outcome: ExecutionOutcome,
+}
+
+/// Ring buffer of the latest executions of a task.
+pub(crate) struct ExecutionHistory {
+    capacity: usize,
+    records: Mutex<VecDeque<ExecutionRecord>>,
+}
+
+impl ExecutionHistory {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Add a record. The oldest record is dropped once the capacity is reached.
+    pub(crate) fn record(&self, record: ExecutionRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// All records, starting with the oldest.
+    pub(crate) fn records(&self) -> Vec<ExecutionRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn record(second: u32) -> ExecutionRecord {
+        let time = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, second).unwrap();
+        ExecutionRecord {
+            scheduled_at: time,
+            started_at: time,
+            finished_at: time,
+            duration: std::time::Duration::ZERO,
+            attempt: 1,
+            outcome: ExecutionOutcome::Succeeded,
+        }
+    }
+
+    #[test]
+    fn that_history_returns_records_starting_with_oldest() {
+        let history = ExecutionHistory::new(5);
+
+        history.record(record(1));
+        history.record(record(2));
+
+        assert_eq!(vec![record(1), record(2)], history.records());
+    }
+
+    #[test]
+    fn that_history_drops_oldest_record_when_capacity_is_reached() {
+        let history = ExecutionHistory::new(2);
+
+        history.record(record(1));
+        history.record(record(2));
+        history.record(record(3));
+
+        assert_eq!(vec![record(2), record(3)], history.records());
+    }
+
+    #[test]
+    fn that_history_without_capacity_keeps_no_records() {
+        let history = ExecutionHistory::new(0);
+
+        history.record(record(1));
+
+        assert!(history.records().is_empty());
+    }
+}
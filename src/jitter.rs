@@ -0,0 +1,161 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// Shifts every occurrence of a task by an offset between zero and `max`, so that instances sharing
+/// the same schedule do not all start at the same time. `max` should be shorter than the interval
+/// between two occurrences, otherwise occurrences can overtake each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Jitter {
+    /// A new random offset for every occurrence. The same seed always yields the same offsets.
+    Random { max: Duration, seed: Option<u64> },
+    /// A fixed offset derived from the key, e.g. the name of the instance.
+    Hashed { max: Duration, key: String },
+}
+
+impl Jitter {
+    pub fn random(max: Duration) -> Self {
+        Jitter::Random { max, seed: None }
+    }
+
+    pub fn seeded(max: Duration, seed: u64) -> Self {
+        Jitter::Random {
+            max,
+            seed: Some(seed),
+        }
+    }
+
+    pub fn hashed(max: Duration, key: impl Into<String>) -> Self {
+        Jitter::Hashed {
+            max,
+            key: key.into(),
+        }
+    }
+}
+
+/// Generates the offsets of a single task.
+pub(crate) struct JitterSource {
+    max_nanos: u64,
+    kind: JitterKind,
+}
+
+enum JitterKind {
+    Random(SplitMix64),
+    Fixed(u64),
+}
+
+impl JitterSource {
+    /// `fallback_seed` is used for random jitter without a seed.
+    pub(crate) fn new(jitter: &Jitter, fallback_seed: u64) -> Self {
+        let (max, kind) = match jitter {
+            Jitter::Random { max, seed } => (
+                max,
+                JitterKind::Random(SplitMix64(seed.unwrap_or(fallback_seed))),
+            ),
+            Jitter::Hashed { max, key } => (
+                max,
+                JitterKind::Fixed(SplitMix64(fnv1a(key.as_bytes())).next()),
+            ),
+        };
+
+        Self {
+            max_nanos: max
+                .num_nanoseconds()
+                .and_then(|nanos| u64::try_from(nanos).ok())
+                .unwrap_or(0),
+            kind,
+        }
+    }
+
+    pub(crate) fn apply(&mut self, time: DateTime<Utc>) -> DateTime<Utc> {
+        time + self.next_offset()
+    }
+
+    fn next_offset(&mut self) -> Duration {
+        if self.max_nanos == 0 {
+            return Duration::zero();
+        }
+
+        let value = match &mut self.kind {
+            JitterKind::Random(random) => random.next(),
+            JitterKind::Fixed(value) => *value,
+        };
+        Duration::nanoseconds((value % (self.max_nanos + 1)) as i64)
+    }
+}
+
+/// Small pseudo random number generator which yields the same numbers on every platform.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Hash which is stable across platforms and Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn offsets(jitter: &Jitter, fallback_seed: u64) -> Vec<Duration> {
+        let time = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let mut source = JitterSource::new(jitter, fallback_seed);
+        (0..20).map(|_| source.apply(time) - time).collect()
+    }
+
+    #[test]
+    fn that_offsets_stay_within_window() {
+        let max = Duration::seconds(30);
+
+        for offset in offsets(&Jitter::random(max), 7) {
+            assert!(offset >= Duration::zero());
+            assert!(offset <= max);
+        }
+    }
+
+    #[test]
+    fn that_seeded_jitter_is_reproducible() {
+        let jitter = Jitter::seeded(Duration::minutes(5), 42);
+
+        let first = offsets(&jitter, 1);
+        let second = offsets(&jitter, 2);
+
+        assert_eq!(first, second);
+        assert!(first.iter().any(|offset| *offset != first[0]));
+    }
+
+    #[test]
+    fn that_random_jitter_without_seed_uses_fallback_seed() {
+        let jitter = Jitter::random(Duration::minutes(5));
+
+        assert_ne!(offsets(&jitter, 1), offsets(&jitter, 2));
+    }
+
+    #[test]
+    fn that_hashed_jitter_is_fixed_per_key() {
+        let first = offsets(&Jitter::hashed(Duration::minutes(5), "instance-a"), 1);
+        let second = offsets(&Jitter::hashed(Duration::minutes(5), "instance-b"), 1);
+
+        assert!(first.iter().all(|offset| *offset == first[0]));
+        assert_ne!(first[0], second[0]);
+    }
+
+    #[test]
+    fn that_zero_window_does_not_shift_time() {
+        let offsets = offsets(&Jitter::random(Duration::zero()), 1);
+
+        assert!(offsets.iter().all(|offset| offset.is_zero()));
+    }
+}
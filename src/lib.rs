@@ -1,4 +1,18 @@
+pub mod calendar;
+pub mod clock;
+pub mod event;
+pub mod exclusion;
+pub mod history;
+pub mod jitter;
+mod limiter;
+pub mod metrics;
+pub mod misfire;
+pub mod overlap;
 pub mod period;
-pub mod zeitschaltuhr;
+pub mod preview;
+pub mod retry;
+pub mod simulation;
+pub mod store;
 pub mod task;
-pub mod temporal_iterator;
\ No newline at end of file
+pub mod temporal_iterator;
+pub mod zeitschaltuhr;
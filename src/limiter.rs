@@ -0,0 +1,209 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::oneshot;
+
+/// Limits the number of executions running at the same time across all tasks. Waiting executions
+/// are admitted by priority, executions with the same priority in the order they started waiting.
+pub(crate) struct ExecutionLimiter {
+    state: Mutex<LimiterState>,
+}
+
+struct LimiterState {
+    available: usize,
+    waiters: BinaryHeap<Waiter>,
+    next_sequence: u64,
+}
+
+struct Waiter {
+    priority: i32,
+    sequence: u64,
+    sender: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    // the greatest waiter is admitted first
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+/// Slot of the limiter which is handed back when the permit is dropped.
+pub(crate) struct LimiterPermit {
+    limiter: Arc<ExecutionLimiter>,
+}
+
+impl Drop for LimiterPermit {
+    fn drop(&mut self) {
+        self.limiter.release();
+    }
+}
+
+/// Waiting for a slot. A slot handed over after the waiting execution was cancelled is passed on.
+struct PendingPermit {
+    limiter: Arc<ExecutionLimiter>,
+    receiver: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for PendingPermit {
+    fn drop(&mut self) {
+        if let Some(mut receiver) = self.receiver.take() {
+            // closing first makes a concurrent release pass the slot to the next waiter, while a
+            // slot which was already handed over is still received
+            receiver.close();
+            if receiver.try_recv().is_ok() {
+                self.limiter.release();
+            }
+        }
+    }
+}
+
+impl ExecutionLimiter {
+    pub(crate) fn new(max_concurrent_executions: usize) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                available: max_concurrent_executions,
+                waiters: BinaryHeap::new(),
+                next_sequence: 0,
+            }),
+        }
+    }
+
+    /// Wait for a free slot. Higher priorities are admitted first.
+    pub(crate) async fn acquire(self: &Arc<Self>, priority: i32) -> LimiterPermit {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 && state.waiters.is_empty() {
+                state.available -= 1;
+                return LimiterPermit {
+                    limiter: self.clone(),
+                };
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state.waiters.push(Waiter {
+                priority,
+                sequence,
+                sender,
+            });
+            receiver
+        };
+
+        let mut pending = PendingPermit {
+            limiter: self.clone(),
+            receiver: Some(receiver),
+        };
+        if let Some(receiver) = pending.receiver.as_mut() {
+            // the sender is only dropped without a slot if the limiter is dropped
+            let _ = receiver.await;
+        }
+        pending.receiver = None;
+
+        LimiterPermit {
+            limiter: self.clone(),
+        }
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        while let Some(waiter) = state.waiters.pop() {
+            // waiters which are not waiting anymore are skipped
+            if waiter.sender.send(()).is_ok() {
+                return;
+            }
+        }
+        state.available += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Let the waiting tasks react. The tests start with a paused tokio clock, so this returns
+    /// once every task is idle.
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_limiter_admits_up_to_maximum_executions() {
+        let limiter = Arc::new(ExecutionLimiter::new(2));
+        let _first = limiter.acquire(0).await;
+        let second = limiter.acquire(0).await;
+
+        let waiting_limiter = limiter.clone();
+        let third = tokio::spawn(async move { waiting_limiter.acquire(0).await });
+        settle().await;
+        assert!(!third.is_finished());
+
+        drop(second);
+        settle().await;
+        assert!(third.is_finished());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_limiter_admits_higher_priority_first_and_equal_priority_in_order() {
+        let limiter = Arc::new(ExecutionLimiter::new(1));
+        let admitted = Arc::new(Mutex::new(Vec::new()));
+        let permit = limiter.acquire(0).await;
+
+        let mut waiting = Vec::new();
+        for (name, priority) in [("low", -1), ("first", 0), ("second", 0), ("high", 5)] {
+            let limiter = limiter.clone();
+            let admitted = admitted.clone();
+            waiting.push(tokio::spawn(async move {
+                let _permit = limiter.acquire(priority).await;
+                admitted.lock().unwrap().push(name);
+            }));
+            settle().await;
+        }
+        drop(permit);
+        for handle in waiting {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(
+            vec!["high", "first", "second", "low"],
+            *admitted.lock().unwrap()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_slot_of_cancelled_waiter_is_passed_on() {
+        let limiter = Arc::new(ExecutionLimiter::new(1));
+        let permit = limiter.acquire(0).await;
+
+        let cancelled_limiter = limiter.clone();
+        let cancelled = tokio::spawn(async move { cancelled_limiter.acquire(0).await });
+        settle().await;
+        cancelled.abort();
+        settle().await;
+        drop(permit);
+
+        let result = tokio::time::timeout(Duration::from_millis(100), limiter.acquire(0)).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use crate::event::{Event, EventListener, SkipReason};
+use crate::misfire::MisfireReason;
+use crate::zeitschaltuhr::TaskId;
+
+/// Upper bounds of the histogram buckets in seconds.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Collects metrics per task from the events of a Zeitschaltuhr and renders them in the Prometheus
+/// text exposition format. Register a clone as listener and keep another one to render the metrics.
+/// Series are labelled with the id of their task and with its name, which falls back to the id.
+/// Tasks may share a name, so only the id identifies a series. Series are dropped once their task
+/// is removed.
+#[derive(Clone, Default)]
+pub struct PrometheusMetrics {
+    tasks: Arc<Mutex<BTreeMap<TaskId, TaskMetrics>>>,
+}
+
+struct TaskMetrics {
+    /// The rendered labels of the series of the task.
+    labels: String,
+    executions: u64,
+    failures: u64,
+    skipped: BTreeMap<&'static str, u64>,
+    execution_duration: Histogram,
+    trigger_latency: Histogram,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl TaskMetrics {
+    fn new(task_id: TaskId, name: Option<&str>) -> Self {
+        let task_id = task_id.to_string();
+        let name = escape_label(name.unwrap_or(&task_id));
+        Self {
+            labels: format!("task_id=\"{task_id}\",task=\"{name}\""),
+            executions: 0,
+            failures: 0,
+            skipped: BTreeMap::new(),
+            execution_duration: Histogram::default(),
+            trigger_latency: Histogram::default(),
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: std::time::Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, upper_bound) in self.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= upper_bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+impl EventListener for PrometheusMetrics {
+    fn on_event(&self, event: &Event) {
+        let mut tasks = self.tasks.lock().unwrap();
+        match event {
+            Event::TaskAdded { task_id, name, .. } => {
+                tasks.insert(*task_id, TaskMetrics::new(*task_id, name.as_deref()));
+                return;
+            }
+            Event::TaskRemoved { task_id, .. } => {
+                tasks.remove(task_id);
+                return;
+            }
+            _ => {}
+        }
+        // executions which finish after their task was removed are not recorded
+        let Some(metrics) = tasks.get_mut(&event.task_id()) else {
+            return;
+        };
+
+        match event {
+            // retries are started after a delay on purpose, which is no latency of the trigger
+            Event::ExecutionStarted {
+                scheduled_at,
+                started_at,
+                attempt: 1,
+                ..
+            } => metrics
+                .trigger_latency
+                .observe((*started_at - *scheduled_at).to_std().unwrap_or_default()),
+            Event::ExecutionStarted { .. } => {}
+            Event::ExecutionFinished { duration, .. } => {
+                metrics.executions += 1;
+                metrics.execution_duration.observe(*duration);
+            }
+            Event::ExecutionFailed { duration, .. } => {
+                metrics.executions += 1;
+                metrics.failures += 1;
+                metrics.execution_duration.observe(*duration);
+            }
+            Event::OccurrenceSkipped { reason, .. } => {
+                *metrics.skipped.entry(reason_label(reason)).or_default() += 1;
+            }
+            Event::TaskAdded { .. }
+            | Event::TaskRemoved { .. }
+            | Event::TriggerFired { .. }
+            | Event::ScheduleExhausted { .. } => {}
+        }
+    }
+}
+
+fn reason_label(reason: &SkipReason) -> &'static str {
+    match reason {
+        SkipReason::Paused => "paused",
+        SkipReason::Overlapping => "overlapping",
+        SkipReason::GroupBusy => "group_busy",
+        SkipReason::Misfire(MisfireReason::Missed) => "missed",
+        SkipReason::Misfire(MisfireReason::TooLate(_)) => "too_late",
+    }
+}
+
+impl PrometheusMetrics {
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let tasks = self.tasks.lock().unwrap();
+        let mut output = String::new();
+
+        write_header(
+            &mut output,
+            "zeitschaltuhr_executions_total",
+            "counter",
+            "Number of finished executions.",
+        );
+        for metrics in tasks.values() {
+            let _ = writeln!(
+                output,
+                "zeitschaltuhr_executions_total{{{}}} {}",
+                metrics.labels, metrics.executions
+            );
+        }
+
+        write_header(
+            &mut output,
+            "zeitschaltuhr_failures_total",
+            "counter",
+            "Number of failed executions.",
+        );
+        for metrics in tasks.values() {
+            let _ = writeln!(
+                output,
+                "zeitschaltuhr_failures_total{{{}}} {}",
+                metrics.labels, metrics.failures
+            );
+        }
+
+        write_header(
+            &mut output,
+            "zeitschaltuhr_skipped_total",
+            "counter",
+            "Number of occurrences which were not executed.",
+        );
+        for metrics in tasks.values() {
+            let labels = &metrics.labels;
+            for (reason, count) in &metrics.skipped {
+                let _ = writeln!(
+                    output,
+                    "zeitschaltuhr_skipped_total{{{labels},reason=\"{reason}\"}} {count}"
+                );
+            }
+        }
+
+        write_header(
+            &mut output,
+            "zeitschaltuhr_execution_duration_seconds",
+            "histogram",
+            "Duration of executions.",
+        );
+        for metrics in tasks.values() {
+            write_histogram(
+                &mut output,
+                "zeitschaltuhr_execution_duration_seconds",
+                &metrics.labels,
+                &metrics.execution_duration,
+            );
+        }
+
+        write_header(
+            &mut output,
+            "zeitschaltuhr_trigger_latency_seconds",
+            "histogram",
+            "Delay between the scheduled time and the start of executions.",
+        );
+        for metrics in tasks.values() {
+            write_histogram(
+                &mut output,
+                "zeitschaltuhr_trigger_latency_seconds",
+                &metrics.labels,
+                &metrics.trigger_latency,
+            );
+        }
+
+        output
+    }
+}
+
+/// Escape a label value according to the text exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_header(output: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(output, "# HELP {name} {help}");
+    let _ = writeln!(output, "# TYPE {name} {kind}");
+}
+
+fn write_histogram(output: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    for (count, upper_bound) in histogram.buckets.iter().zip(BUCKETS) {
+        let _ = writeln!(
+            output,
+            "{name}_bucket{{{labels},le=\"{upper_bound}\"}} {count}"
+        );
+    }
+    let _ = writeln!(
+        output,
+        "{name}_bucket{{{labels},le=\"+Inf\"}} {}",
+        histogram.count
+    );
+    let _ = writeln!(output, "{name}_sum{{{labels}}} {}", histogram.sum);
+    let _ = writeln!(output, "{name}_count{{{labels}}} {}", histogram.count);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    use super::*;
+
+    fn task_id() -> TaskId {
+        TaskId::new(0)
+    }
+
+    fn time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn added(task_id: TaskId, name: Option<&str>) -> Event {
+        Event::TaskAdded {
+            task_id,
+            name: name.map(str::to_string),
+            added_at: time(),
+        }
+    }
+
+    #[test]
+    fn that_executions_and_failures_are_counted() {
+        let metrics = PrometheusMetrics::default();
+        let task_id = task_id();
+        let time = time();
+
+        metrics.on_event(&added(task_id, None));
+        // the duration is measured in real time, while the clock of the Zeitschaltuhr may stand still
+        metrics.on_event(&Event::ExecutionFinished {
+            task_id,
+            scheduled_at: time,
+            started_at: time,
+            finished_at: time,
+            duration: std::time::Duration::from_millis(20),
+            attempt: 1,
+        });
+        metrics.on_event(&Event::ExecutionFailed {
+            task_id,
+            scheduled_at: time,
+            started_at: time,
+            finished_at: time,
+            duration: std::time::Duration::from_secs(3),
+            attempt: 1,
+            error: "error".to_string(),
+        });
+        let output = metrics.render();
+
+        assert!(output
+            .contains("zeitschaltuhr_executions_total{task_id=\"task-0\",task=\"task-0\"} 2\n"));
+        assert!(
+            output.contains("zeitschaltuhr_failures_total{task_id=\"task-0\",task=\"task-0\"} 1\n")
+        );
+        assert!(output.contains(
+            "zeitschaltuhr_execution_duration_seconds_bucket{task_id=\"task-0\",task=\"task-0\",le=\"0.025\"} 1\n"
+        ));
+        assert!(output.contains(
+            "zeitschaltuhr_execution_duration_seconds_bucket{task_id=\"task-0\",task=\"task-0\",le=\"5\"} 2\n"
+        ));
+        assert!(output.contains(
+            "zeitschaltuhr_execution_duration_seconds_bucket{task_id=\"task-0\",task=\"task-0\",le=\"+Inf\"} 2\n"
+        ));
+        assert!(
+            output.contains("zeitschaltuhr_execution_duration_seconds_sum{task_id=\"task-0\",task=\"task-0\"} 3.02\n")
+        );
+        assert!(
+            output.contains("zeitschaltuhr_execution_duration_seconds_count{task_id=\"task-0\",task=\"task-0\"} 2\n")
+        );
+    }
+
+    #[test]
+    fn that_skipped_occurrences_and_trigger_latency_are_recorded() {
+        let metrics = PrometheusMetrics::default();
+        let task_id = task_id();
+        let time = time();
+
+        metrics.on_event(&added(task_id, None));
+        metrics.on_event(&Event::OccurrenceSkipped {
+            task_id,
+            scheduled_at: time,
+            skipped_at: time,
+            reason: SkipReason::Misfire(MisfireReason::Missed),
+        });
+        metrics.on_event(&Event::ExecutionStarted {
+            task_id,
+            scheduled_at: time,
+            started_at: time + Duration::milliseconds(200),
+            attempt: 1,
+        });
+        let output = metrics.render();
+
+        assert!(output.contains(
+            "zeitschaltuhr_skipped_total{task_id=\"task-0\",task=\"task-0\",reason=\"missed\"} 1\n"
+        ));
+        assert!(output.contains(
+            "zeitschaltuhr_trigger_latency_seconds_bucket{task_id=\"task-0\",task=\"task-0\",le=\"0.1\"} 0\n"
+        ));
+        assert!(output.contains(
+            "zeitschaltuhr_trigger_latency_seconds_bucket{task_id=\"task-0\",task=\"task-0\",le=\"0.25\"} 1\n"
+        ));
+    }
+
+    #[test]
+    fn that_series_are_labelled_with_the_name_of_the_task() {
+        let metrics = PrometheusMetrics::default();
+        let task_id = task_id();
+
+        metrics.on_event(&added(task_id, Some("nightly \"backup\"")));
+        let output = metrics.render();
+
+        assert!(output.contains(
+            "zeitschaltuhr_executions_total{task_id=\"task-0\",task=\"nightly \\\"backup\\\"\"} 0\n"
+        ));
+    }
+
+    #[test]
+    fn that_tasks_with_the_same_name_have_separate_series() {
+        let metrics = PrometheusMetrics::default();
+
+        metrics.on_event(&added(TaskId::new(0), Some("backup")));
+        metrics.on_event(&added(TaskId::new(1), Some("backup")));
+        let output = metrics.render();
+
+        assert!(output
+            .contains("zeitschaltuhr_executions_total{task_id=\"task-0\",task=\"backup\"} 0\n"));
+        assert!(output
+            .contains("zeitschaltuhr_executions_total{task_id=\"task-1\",task=\"backup\"} 0\n"));
+    }
+
+    #[test]
+    fn that_trigger_latency_is_not_recorded_for_retries() {
+        let metrics = PrometheusMetrics::default();
+        let task_id = task_id();
+        let time = time();
+
+        metrics.on_event(&added(task_id, None));
+        metrics.on_event(&Event::ExecutionStarted {
+            task_id,
+            scheduled_at: time,
+            started_at: time + Duration::minutes(5),
+            attempt: 2,
+        });
+        let output = metrics.render();
+
+        assert!(output.contains(
+            "zeitschaltuhr_trigger_latency_seconds_count{task_id=\"task-0\",task=\"task-0\"} 0\n"
+        ));
+    }
+
+    #[test]
+    fn that_series_of_removed_tasks_are_dropped() {
+        let metrics = PrometheusMetrics::default();
+        let task_id = task_id();
+
+        metrics.on_event(&added(task_id, None));
+        metrics.on_event(&Event::TaskRemoved {
+            task_id,
+            removed_at: time(),
+        });
+        metrics.on_event(&Event::ExecutionFinished {
+            task_id,
+            scheduled_at: time(),
+            started_at: time(),
+            finished_at: time(),
+            duration: std::time::Duration::from_millis(20),
+            attempt: 1,
+        });
+        let output = metrics.render();
+
+        assert!(!output.contains("task-0"));
+    }
+}
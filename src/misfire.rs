@@ -0,0 +1,157 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// Decides what happens when several occurrences of a task are due at once, e.g. after the
+/// process was suspended or the runtime was stalled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MisfirePolicy {
+    /// Execute only the latest of the due occurrences.
+    FireOnce,
+    /// Execute every due occurrence.
+    #[default]
+    FireAll,
+    /// Execute none of the due occurrences and wait for the next one.
+    SkipToNext,
+}
+
+/// Reason why an occurrence was dropped without being executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MisfireReason {
+    /// Further occurrences were due at the same time and the misfire policy did not fire this one.
+    Missed,
+    /// The occurrence was due for longer than the maximum lateness of the task.
+    TooLate(Duration),
+}
+
+/// Occurrences which are due at the same time, split into the ones to execute and the ones to drop.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct DueOccurrences {
+    pub(crate) fire: Vec<DateTime<Utc>>,
+    pub(crate) dropped: Vec<(DateTime<Utc>, MisfireReason)>,
+}
+
+impl MisfirePolicy {
+    /// Decide which of the `due` occurrences are executed. `due` has to be sorted in ascending order.
+    /// Occurrences which are late by more than `max_lateness` are always dropped.
+    pub(crate) fn resolve(
+        &self,
+        due: Vec<DateTime<Utc>>,
+        max_lateness: Option<Duration>,
+        now: DateTime<Utc>,
+    ) -> DueOccurrences {
+        let mut result = DueOccurrences::default();
+        let mut in_time = Vec::with_capacity(due.len());
+        for time in due {
+            let lateness = now - time;
+            match max_lateness {
+                Some(max_lateness) if lateness > max_lateness => result
+                    .dropped
+                    .push((time, MisfireReason::TooLate(lateness))),
+                _ => in_time.push(time),
+            }
+        }
+
+        if in_time.len() <= 1 {
+            result.fire = in_time;
+            return result;
+        }
+
+        match self {
+            MisfirePolicy::FireAll => result.fire = in_time,
+            MisfirePolicy::FireOnce => {
+                let latest = in_time.pop();
+                result.dropped.extend(
+                    in_time
+                        .into_iter()
+                        .map(|time| (time, MisfireReason::Missed)),
+                );
+                result.fire.extend(latest);
+            }
+            MisfirePolicy::SkipToNext => result.dropped.extend(
+                in_time
+                    .into_iter()
+                    .map(|time| (time, MisfireReason::Missed)),
+            ),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2020, 1, 1, 0, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn that_single_due_occurrence_is_fired_regardless_of_policy() {
+        for policy in [
+            MisfirePolicy::FireOnce,
+            MisfirePolicy::FireAll,
+            MisfirePolicy::SkipToNext,
+        ] {
+            let result = policy.resolve(vec![at(0)], None, at(1));
+
+            assert_eq!(vec![at(0)], result.fire);
+            assert!(result.dropped.is_empty());
+        }
+    }
+
+    #[test]
+    fn that_fire_all_fires_every_due_occurrence() {
+        let result = MisfirePolicy::FireAll.resolve(vec![at(0), at(1), at(2)], None, at(2));
+
+        assert_eq!(vec![at(0), at(1), at(2)], result.fire);
+        assert!(result.dropped.is_empty());
+    }
+
+    #[test]
+    fn that_fire_once_fires_latest_due_occurrence() {
+        let result = MisfirePolicy::FireOnce.resolve(vec![at(0), at(1), at(2)], None, at(2));
+
+        assert_eq!(vec![at(2)], result.fire);
+        assert_eq!(
+            vec![
+                (at(0), MisfireReason::Missed),
+                (at(1), MisfireReason::Missed)
+            ],
+            result.dropped
+        );
+    }
+
+    #[test]
+    fn that_skip_to_next_fires_nothing_when_occurrences_were_missed() {
+        let result = MisfirePolicy::SkipToNext.resolve(vec![at(0), at(1)], None, at(1));
+
+        assert!(result.fire.is_empty());
+        assert_eq!(
+            vec![
+                (at(0), MisfireReason::Missed),
+                (at(1), MisfireReason::Missed)
+            ],
+            result.dropped
+        );
+    }
+
+    #[test]
+    fn that_occurrences_exceeding_max_lateness_are_dropped() {
+        let result = MisfirePolicy::FireAll.resolve(
+            vec![at(0), at(5), at(9)],
+            Some(Duration::minutes(2)),
+            at(10),
+        );
+
+        assert_eq!(vec![at(9)], result.fire);
+        assert_eq!(
+            vec![
+                (at(0), MisfireReason::TooLate(Duration::minutes(10))),
+                (at(5), MisfireReason::TooLate(Duration::minutes(5)))
+            ],
+            result.dropped
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Decides what happens with an occurrence which becomes due while a previous execution of the
+/// same task is still running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlapPolicy {
+    /// Skip the occurrence.
+    Skip,
+    /// Run the occurrence once the previous execution has finished. Occurrences are skipped
+    /// instead while the previous execution is still running after its timeout.
+    #[default]
+    Queue,
+    /// Run up to the given number of executions at the same time. Further occurrences are skipped.
+    Concurrent(NonZeroUsize),
+}
+
+impl OverlapPolicy {
+    /// Number of executions which may run at the same time.
+    fn permits(&self) -> usize {
+        match self {
+            OverlapPolicy::Skip | OverlapPolicy::Queue => 1,
+            OverlapPolicy::Concurrent(permits) => permits.get(),
+        }
+    }
+
+    /// Decide when an occurrence which is due at `now` starts, given the end times of the executions
+    /// which were started before. Returns None if the occurrence has to be skipped. Used to simulate
+    /// executions instead of waiting for them.
+    pub(crate) fn admit(
+        &self,
+        now: DateTime<Utc>,
+        running: &mut Vec<DateTime<Utc>>,
+    ) -> Option<DateTime<Utc>> {
+        running.retain(|finished_at| *finished_at > now);
+        if running.len() < self.permits() {
+            return Some(now);
+        }
+
+        match self {
+            OverlapPolicy::Queue => {
+                let (index, finished_at) = running
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .min_by_key(|(_, finished_at)| *finished_at)?;
+                running.remove(index);
+                Some(finished_at)
+            }
+            OverlapPolicy::Skip | OverlapPolicy::Concurrent(_) => None,
+        }
+    }
+}
+
+/// Hands out permits to execute a task according to its overlap policy. Clones share the permits.
+#[derive(Clone)]
+pub(crate) struct OverlapGuard {
+    policy: OverlapPolicy,
+    semaphore: Arc<Semaphore>,
+}
+
+impl OverlapGuard {
+    pub(crate) fn new(policy: OverlapPolicy) -> Self {
+        Self {
+            policy,
+            semaphore: Arc::new(Semaphore::new(policy.permits())),
+        }
+    }
+
+    /// Permit for the next execution if one is available right away.
+    pub(crate) fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        self.semaphore.clone().try_acquire_owned().ok()
+    }
+
+    /// Whether occurrences wait for the previous execution instead of being skipped.
+    pub(crate) fn queues(&self) -> bool {
+        self.policy == OverlapPolicy::Queue
+    }
+
+    /// Permit for the next execution. Waits for the previous execution if occurrences are queued
+    /// and returns None if the occurrence has to be skipped.
+    pub(crate) async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        match self.policy {
+            OverlapPolicy::Queue => self.semaphore.clone().acquire_owned().await.ok(),
+            OverlapPolicy::Skip | OverlapPolicy::Concurrent(_) => {
+                self.semaphore.clone().try_acquire_owned().ok()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[tokio::test]
+    async fn that_skip_policy_skips_while_execution_is_running() {
+        let guard = OverlapGuard::new(OverlapPolicy::Skip);
+
+        let permit = guard.acquire().await;
+        assert!(permit.is_some());
+        assert!(guard.acquire().await.is_none());
+
+        drop(permit);
+        assert!(guard.acquire().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn that_concurrent_policy_allows_up_to_given_number_of_executions() {
+        let guard = OverlapGuard::new(OverlapPolicy::Concurrent(NonZeroUsize::new(2).unwrap()));
+
+        let first = guard.acquire().await;
+        let second = guard.acquire().await;
+
+        assert!(first.is_some());
+        assert!(second.is_some());
+        assert!(guard.acquire().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn that_queue_policy_waits_for_previous_execution() {
+        let guard = Arc::new(OverlapGuard::new(OverlapPolicy::Queue));
+        let permit = guard.acquire().await.unwrap();
+
+        let waiting_guard = guard.clone();
+        let waiting = tokio::spawn(async move { waiting_guard.acquire().await.is_some() });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(permit);
+        assert!(waiting.await.unwrap());
+    }
+
+    #[test]
+    fn that_admit_starts_occurrence_immediately_when_nothing_is_running() {
+        let now = Utc::now();
+        let mut running = vec![now - chrono::Duration::seconds(1)];
+
+        let started_at = OverlapPolicy::Skip.admit(now, &mut running);
+
+        assert_eq!(Some(now), started_at);
+        assert!(running.is_empty());
+    }
+
+    #[test]
+    fn that_admit_queues_occurrence_until_previous_execution_finished() {
+        let now = Utc::now();
+        let finished_at = now + chrono::Duration::seconds(5);
+        let mut running = vec![finished_at];
+
+        let queued = OverlapPolicy::Queue.admit(now, &mut running);
+        let skipped = OverlapPolicy::Skip.admit(now, &mut vec![finished_at]);
+
+        assert_eq!(Some(finished_at), queued);
+        assert_eq!(None, skipped);
+    }
+
+    #[test]
+    fn that_admit_allows_concurrent_executions_up_to_limit() {
+        let now = Utc::now();
+        let finished_at = now + chrono::Duration::seconds(5);
+        let policy = OverlapPolicy::Concurrent(NonZeroUsize::new(2).unwrap());
+
+        let first = policy.admit(now, &mut vec![finished_at]);
+        let second = policy.admit(now, &mut vec![finished_at, finished_at]);
+
+        assert_eq!(Some(now), first);
+        assert_eq!(None, second);
+    }
+}
@@ -1,10 +1,26 @@
+use crate::clock::{Clock, SystemClock};
 use chrono::{DateTime, Duration, DurationRound, TimeZone, Utc};
 use std::cmp::Ordering;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct Period {
     start: DateTime<Utc>,
     duration: Duration,
+    precision: Precision,
+    end: Option<DateTime<Utc>>,
+    count: Option<u32>,
+    /// Derived from `end` and `count`, so it is not computed for every occurrence.
+    last_occurrence: Option<DateTime<Utc>>,
+    clock: Arc<dyn Clock>,
+}
+
+/// The unit the start and the duration of a Period are rounded to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Precision {
+    #[default]
+    Seconds,
+    Milliseconds,
 }
 
 #[derive(Debug)]
@@ -20,8 +36,18 @@ impl Period {
         start: DateTime<T>,
         duration: Duration,
     ) -> Result<Self, PeriodError> {
-        let start = adjust_timestamp(start.to_utc());
-        let duration = adjust_duration(duration);
+        Self::starting_at_with_precision(start, duration, Precision::Seconds)
+    }
+
+    /// Create a Period where the starting timestamp and the duration are adjusted to the nearest unit of `precision`.
+    /// Fails if the duration is zero or negative after the adjustment.
+    pub fn starting_at_with_precision<T: TimeZone>(
+        start: DateTime<T>,
+        duration: Duration,
+        precision: Precision,
+    ) -> Result<Self, PeriodError> {
+        let start = adjust_timestamp(start.to_utc(), precision);
+        let duration = adjust_duration(duration, precision);
 
         if duration.is_zero() {
             Err(PeriodError::ZeroDurationError)
@@ -30,15 +56,77 @@ impl Period {
         {
             Err(PeriodError::NegativeDurationError)
         } else {
-            Ok(Period { start, duration })
+            Ok(Period {
+                start,
+                duration,
+                precision,
+                end: None,
+                count: None,
+                last_occurrence: None,
+                clock: Arc::new(SystemClock),
+            })
         }
     }
 
-    pub fn upcoming_relative(&self) -> PeriodIterator {
+    /// Use the clock to decide which timestamps are upcoming. Defaults to the system clock.
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// End the Period with the last occurrence at or before `end`, like the UNTIL of a RRULE.
+    pub fn with_end<T: TimeZone>(mut self, end: DateTime<T>) -> Self {
+        self.end = Some(end.to_utc());
+        self.last_occurrence = self.compute_last_occurrence();
+        self
+    }
+
+    /// End the Period after `count` occurrences counted from its start, like the COUNT of a RRULE.
+    pub fn with_count(mut self, count: u32) -> Self {
+        self.count = Some(count);
+        self.last_occurrence = self.compute_last_occurrence();
+        self
+    }
+
+    /// No occurrences are generated after this timestamp. `None` if the Period repeats forever.
+    fn last_occurrence(&self) -> Option<DateTime<Utc>> {
+        self.last_occurrence
+    }
+
+    /// A bound beyond the range of `DateTime` is never reached, so it is treated as no bound.
+    fn compute_last_occurrence(&self) -> Option<DateTime<Utc>> {
+        let duration = self.duration.num_milliseconds();
+        let last_counted = self.count.and_then(|count| {
+            let elapsed = duration.checked_mul(i64::from(count) - 1)?;
+            self.start
+                .checked_add_signed(Duration::try_milliseconds(elapsed)?)
+        });
+        let last_before_end = self.end.and_then(|end| {
+            if end < self.start {
+                Some(end)
+            } else {
+                let elapsed_durations = (end - self.start).num_milliseconds() / duration;
+                let elapsed = duration.checked_mul(elapsed_durations)?;
+                self.start
+                    .checked_add_signed(Duration::try_milliseconds(elapsed)?)
+            }
+        });
+        match (last_before_end, last_counted) {
+            (Some(last_before_end), Some(last_counted)) => Some(last_before_end.min(last_counted)),
+            (last_before_end, last_counted) => last_before_end.or(last_counted),
+        }
+    }
+
+    fn includes(&self, timestamp: &DateTime<Utc>) -> bool {
+        self.last_occurrence()
+            .is_none_or(|last_occurrence| *timestamp <= last_occurrence)
+    }
+
+    pub fn upcoming_relative(&self) -> PeriodIterator<'_> {
         PeriodIterator::new_relative(self)
     }
 
-    pub fn upcoming_fixed(&self) -> PeriodIterator {
+    pub fn upcoming_fixed(&self) -> PeriodIterator<'_> {
         PeriodIterator::new_fixed(self)
     }
 
@@ -51,6 +139,16 @@ impl Period {
     pub fn upcoming_fixed_owned(self) -> OwnedPeriodIterator {
         OwnedPeriodIterator::new_fixed(self)
     }
+
+    /// Return an iterator of DateTimes that takes ownership of the Period. That iterator will only generate values from `now` on.
+    pub fn upcoming_relative_owned_at(self, now: DateTime<Utc>) -> OwnedPeriodIterator {
+        OwnedPeriodIterator::new_relative_at(self, now)
+    }
+
+    /// Return an iterator of DateTimes that takes ownership of the Period. That iterator will only generate values after `after`.
+    pub fn upcoming_after_owned(self, after: DateTime<Utc>) -> OwnedPeriodIterator {
+        OwnedPeriodIterator::new_after(self, after)
+    }
 }
 
 pub struct PeriodIterator<'a> {
@@ -73,7 +171,9 @@ impl<'a> PeriodIterator<'a> {
 
     /// Create an iterator for the period, which will only generate values after the current timestamp.
     fn new_relative(period: &'a Period) -> Self {
-        let start = next_available_timestamp(period.start, &period.duration).unwrap();
+        let now = period.clock.now();
+        let start = next_available_timestamp(period.start, &period.duration, now, period.precision)
+            .unwrap();
         Self::new(period, start)
     }
 }
@@ -82,9 +182,12 @@ impl Iterator for PeriodIterator<'_> {
     type Item = DateTime<Utc>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.current.take().inspect(|current| {
-            self.current = Some(*current + self.period.duration);
-        })
+        self.current
+            .take()
+            .filter(|current| self.period.includes(current))
+            .inspect(|current| {
+                self.current = current.checked_add_signed(self.period.duration);
+            })
     }
 }
 
@@ -109,7 +212,20 @@ impl OwnedPeriodIterator {
 
     /// Create an iterator for the period, which will only generate values after the current timestamp.
     fn new_relative(period: Period) -> Self {
-        let start = next_available_timestamp(period.start, &period.duration).unwrap();
+        let now = period.clock.now();
+        Self::new_relative_at(period, now)
+    }
+
+    /// Create an iterator for the period, which will only generate values after the given current timestamp.
+    fn new_relative_at(period: Period, now: DateTime<Utc>) -> Self {
+        let start = next_available_timestamp(period.start, &period.duration, now, period.precision)
+            .unwrap();
+        Self::new(period, start)
+    }
+
+    /// Create an iterator for the period, which will only generate values after the given timestamp.
+    fn new_after(period: Period, after: DateTime<Utc>) -> Self {
+        let start = first_timestamp_after(period.start, &period.duration, after);
         Self::new(period, start)
     }
 }
@@ -118,39 +234,79 @@ impl Iterator for OwnedPeriodIterator {
     type Item = DateTime<Utc>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.current.take().inspect(|current| {
-            self.current = Some(*current + self.period.duration);
-        })
+        self.current
+            .take()
+            .filter(|current| self.period.includes(current))
+            .inspect(|current| {
+                self.current = current.checked_add_signed(self.period.duration);
+            })
     }
 }
 
-// Adjust timestamp to closest full second
-fn adjust_timestamp(timestamp: DateTime<Utc>) -> DateTime<Utc> {
-    timestamp.duration_round(Duration::seconds(1)).unwrap()
+impl Precision {
+    fn unit(self) -> Duration {
+        match self {
+            Precision::Seconds => Duration::seconds(1),
+            Precision::Milliseconds => Duration::milliseconds(1),
+        }
+    }
 }
 
-/// Adjust duration to closest full second
-fn adjust_duration(duration: Duration) -> Duration {
-    Duration::seconds(duration.as_seconds_f64().round() as i64)
+// Adjust timestamp to closest full unit of the precision
+fn adjust_timestamp(timestamp: DateTime<Utc>, precision: Precision) -> DateTime<Utc> {
+    timestamp.duration_round(precision.unit()).unwrap()
 }
 
-fn next_available_timestamp<T>(timestamp: DateTime<T>, duration: &Duration) -> Option<DateTime<T>>
+/// Adjust duration to closest full unit of the precision
+fn adjust_duration(duration: Duration, precision: Precision) -> Duration {
+    match precision {
+        Precision::Seconds => Duration::seconds(duration.as_seconds_f64().round() as i64),
+        Precision::Milliseconds => {
+            Duration::milliseconds((duration.as_seconds_f64() * 1_000.0).round() as i64)
+        }
+    }
+}
+
+fn next_available_timestamp<T>(
+    timestamp: DateTime<T>,
+    duration: &Duration,
+    now: DateTime<Utc>,
+    precision: Precision,
+) -> Option<DateTime<T>>
 where
     T: TimeZone,
 {
-    let seconds_from_timestamp = adjust_timestamp(Utc::now()).timestamp() - timestamp.timestamp();
+    let millis_from_timestamp =
+        adjust_timestamp(now, precision).timestamp_millis() - timestamp.timestamp_millis();
 
-    Some(match seconds_from_timestamp.cmp(&0) {
+    Some(match millis_from_timestamp.cmp(&0) {
         Ordering::Less => timestamp.clone(),
         Ordering::Equal => timestamp.clone() + *duration,
         Ordering::Greater => {
             let elapsed_durations =
-                (seconds_from_timestamp as u32).div_ceil(duration.num_seconds() as u32) as i32;
-            timestamp.clone() + duration.checked_mul(elapsed_durations).unwrap()
+                (millis_from_timestamp as u64).div_ceil(duration.num_milliseconds() as u64) as i64;
+            let elapsed = duration
+                .num_milliseconds()
+                .checked_mul(elapsed_durations)
+                .and_then(Duration::try_milliseconds)?;
+            timestamp.clone() + elapsed
         }
     })
 }
 
+fn first_timestamp_after(
+    timestamp: DateTime<Utc>,
+    duration: &Duration,
+    after: DateTime<Utc>,
+) -> DateTime<Utc> {
+    if after < timestamp {
+        return timestamp;
+    }
+    let elapsed_durations =
+        (after - timestamp).num_milliseconds() / duration.num_milliseconds() + 1;
+    timestamp + Duration::milliseconds(duration.num_milliseconds() * elapsed_durations)
+}
+
 #[cfg(test)]
 #[path = "./period/tests.rs"]
 mod tests;
@@ -1,6 +1,7 @@
-use chrono::{Datelike, Days, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono::{Days, Duration, NaiveDate, TimeZone, Utc};
 use chrono_tz::Europe::Berlin;
 
+use crate::clock::MockClock;
 use crate::period::*;
 
 #[test]
@@ -119,27 +120,52 @@ fn that_upcoming_relative_owned_returns_iterator() {
 fn that_relative_iterator_adjusts_initial_value_to_be_in_the_future_when_start_is_in_the_past() {
     let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
     let duration = Duration::days(1);
-    let period = Period::starting_at(timestamp, duration).unwrap();
+    let now = Utc.with_ymd_and_hms(2024, 6, 15, 13, 45, 10).unwrap();
+    let period = Period::starting_at(timestamp, duration)
+        .unwrap()
+        .with_clock(MockClock::new(now));
     let iterator = period.upcoming_relative_owned();
-    let now = Utc::now();
 
     let result = iterator.current.unwrap();
 
-    assert!(result > now);
+    assert_eq!(result, Utc.with_ymd_and_hms(2024, 6, 16, 0, 0, 0).unwrap());
+}
+
+#[test]
+fn that_relative_iterator_follows_the_clock_of_the_period() {
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let clock = MockClock::new(Utc.with_ymd_and_hms(2020, 1, 1, 0, 30, 0).unwrap());
+    let period = Period::starting_at(timestamp, Duration::hours(1))
+        .unwrap()
+        .with_clock(clock.clone());
+
+    let first = period.upcoming_relative().next().unwrap();
+    clock.advance(Duration::hours(2));
+    let second = period.upcoming_relative().next().unwrap();
+
+    assert_eq!(first, Utc.with_ymd_and_hms(2020, 1, 1, 1, 0, 0).unwrap());
+    assert_eq!(second, Utc.with_ymd_and_hms(2020, 1, 1, 3, 0, 0).unwrap());
+}
+
+#[test]
+fn that_relative_owned_iterator_at_starts_from_the_given_time() {
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let period = Period::starting_at(timestamp, Duration::hours(1)).unwrap();
+    let now = Utc.with_ymd_and_hms(2020, 1, 1, 5, 10, 0).unwrap();
+
+    let mut iterator = period.upcoming_relative_owned_at(now);
+
     assert_eq!(
-        result.day(),
-        now.checked_add_days(Days::new(1)).unwrap().day()
+        iterator.next(),
+        Utc.with_ymd_and_hms(2020, 1, 1, 6, 0, 0).single()
     );
-    assert_eq!(result.hour(), 0);
-    assert_eq!(result.minute(), 0);
-    assert_eq!(result.second(), 0);
 }
 
 #[test]
 fn that_relative_iterator_adjusts_initial_value_to_be_in_the_future_when_start_is_now() {
     let duration = Duration::days(1);
     let period = Period::starting_at(Utc::now(), duration).unwrap();
-    let start = period.start.clone();
+    let start = period.start;
     let iterator = period.upcoming_relative_owned();
 
     let current = iterator.current.unwrap();
@@ -152,7 +178,7 @@ fn that_relative_iterator_does_not_adjust_initial_value_when_start_is_in_the_fut
     let timestamp = Utc::now().checked_add_days(Days::new(10)).unwrap();
     let duration = Duration::days(1);
     let period = Period::starting_at(timestamp, duration).unwrap();
-    let start = period.start.clone();
+    let start = period.start;
     let iterator = period.upcoming_relative_owned();
 
     let current = iterator.current.unwrap();
@@ -250,9 +276,9 @@ fn that_duration_between_data_points_is_unaffected_by_end_of_daylight_savings()
 #[test]
 fn that_adjust_timestamp_does_not_adjust_timestamp() {
     let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
-    let expected = timestamp.clone();
+    let expected = timestamp;
 
-    let result = adjust_timestamp(timestamp);
+    let result = adjust_timestamp(timestamp, Precision::Seconds);
 
     assert_eq!(expected, result);
 }
@@ -266,7 +292,7 @@ fn that_adjust_timestamp_rounds_down_to_full_second() {
         .and_utc();
     let expected = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
 
-    let result = adjust_timestamp(timestamp);
+    let result = adjust_timestamp(timestamp, Precision::Seconds);
 
     assert_eq!(expected, result);
 }
@@ -280,7 +306,7 @@ fn that_adjust_timestamp_rounds_up_to_full_second() {
         .and_utc();
     let expected = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 1).unwrap();
 
-    let result = adjust_timestamp(timestamp);
+    let result = adjust_timestamp(timestamp, Precision::Seconds);
 
     assert_eq!(expected, result);
 }
@@ -290,7 +316,7 @@ fn that_adjust_duration_does_no_adjustments() {
     let duration = Duration::seconds(3);
     let expected_result = Duration::seconds(3);
 
-    let result = adjust_duration(duration);
+    let result = adjust_duration(duration, Precision::Seconds);
 
     assert_eq!(expected_result, result);
 }
@@ -301,7 +327,7 @@ fn that_adjust_duration_rounds_down_to_lower_second() {
     let duration = Duration::microseconds(1_200_234);
     let expected_result = Duration::seconds(1);
 
-    let result = adjust_duration(duration);
+    let result = adjust_duration(duration, Precision::Seconds);
 
     assert_eq!(expected_result, result);
 }
@@ -312,7 +338,7 @@ fn that_adjust_duration_rounds_up_to_next_second() {
     let duration = Duration::milliseconds(1_500);
     let expected_result = Duration::seconds(2);
 
-    let result = adjust_duration(duration);
+    let result = adjust_duration(duration, Precision::Seconds);
 
     assert_eq!(expected_result, result);
 }
@@ -323,7 +349,7 @@ fn that_adjust_duration_rounds_to_higher_second_for_negative_duration() {
     let duration = Duration::milliseconds(-1_200);
     let expected_result = Duration::seconds(-1);
 
-    let result = adjust_duration(duration);
+    let result = adjust_duration(duration, Precision::Seconds);
 
     assert_eq!(expected_result, result);
 }
@@ -334,17 +360,18 @@ fn that_adjust_duration_rounds_to_lower_second_for_negative_duration() {
     let duration = Duration::milliseconds(-1_500);
     let expected_result = Duration::seconds(-2);
 
-    let result = adjust_duration(duration);
+    let result = adjust_duration(duration, Precision::Seconds);
 
     assert_eq!(expected_result, result);
 }
 
 #[test]
 fn that_next_available_timestamp_returns_value_in_the_future_when_timestamp_is_now() {
-    let timestamp = Utc::now();
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
     let duration = Duration::seconds(20);
 
-    let result = next_available_timestamp(timestamp, &duration).unwrap();
+    let result =
+        next_available_timestamp(timestamp, &duration, timestamp, Precision::Seconds).unwrap();
 
     assert_eq!(result, timestamp + duration);
 }
@@ -354,26 +381,225 @@ fn that_next_available_timestamp_returns_adjusted_value_in_the_future_when_times
 ) {
     let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
     let duration = Duration::days(1);
+    let now = Utc.with_ymd_and_hms(2024, 2, 28, 21, 3, 7).unwrap();
 
-    let result = next_available_timestamp(timestamp, &duration).unwrap();
+    let result = next_available_timestamp(timestamp, &duration, now, Precision::Seconds).unwrap();
 
-    assert!(result > timestamp);
-    assert_eq!(
-        result.day(),
-        Utc::now().checked_add_days(Days::new(1)).unwrap().day()
-    );
-    assert_eq!(result.hour(), 0);
-    assert_eq!(result.minute(), 0);
-    assert_eq!(result.second(), 0);
+    assert_eq!(result, Utc.with_ymd_and_hms(2024, 2, 29, 0, 0, 0).unwrap());
 }
 
 #[test]
 fn that_next_available_timestamp_returns_timestamp_in_the_future_when_timestamp_lies_in_the_future()
 {
-    let timestamp = Utc::now().checked_add_days(Days::new(10)).unwrap();
+    let now = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let timestamp = now.checked_add_days(Days::new(10)).unwrap();
     let duration = Duration::days(1);
 
-    let result = next_available_timestamp(timestamp, &duration).unwrap();
+    let result = next_available_timestamp(timestamp, &duration, now, Precision::Seconds).unwrap();
 
     assert!(result == timestamp);
 }
+
+#[test]
+fn that_first_timestamp_after_returns_timestamp_when_it_lies_after_the_given_time() {
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let after = Utc.with_ymd_and_hms(2019, 12, 31, 0, 0, 0).unwrap();
+
+    let result = first_timestamp_after(timestamp, &Duration::hours(1), after);
+
+    assert_eq!(result, timestamp);
+}
+
+#[test]
+fn that_first_timestamp_after_skips_the_occurrence_at_the_given_time() {
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let after = Utc.with_ymd_and_hms(2020, 1, 1, 3, 0, 0).unwrap();
+
+    let result = first_timestamp_after(timestamp, &Duration::hours(1), after);
+
+    assert_eq!(result, Utc.with_ymd_and_hms(2020, 1, 1, 4, 0, 0).unwrap());
+}
+
+#[test]
+fn that_first_timestamp_after_returns_next_occurrence_between_occurrences() {
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let after = Utc.with_ymd_and_hms(2020, 1, 1, 3, 20, 0).unwrap();
+
+    let result = first_timestamp_after(timestamp, &Duration::hours(1), after);
+
+    assert_eq!(result, Utc.with_ymd_and_hms(2020, 1, 1, 4, 0, 0).unwrap());
+}
+
+#[test]
+fn that_period_with_millisecond_precision_keeps_milliseconds() {
+    let start = NaiveDate::from_ymd_opt(2020, 1, 1)
+        .unwrap()
+        .and_hms_micro_opt(0, 0, 0, 250_400)
+        .unwrap()
+        .and_utc();
+    let expected_start = NaiveDate::from_ymd_opt(2020, 1, 1)
+        .unwrap()
+        .and_hms_milli_opt(0, 0, 0, 250)
+        .unwrap()
+        .and_utc();
+    let duration = Duration::microseconds(100_600);
+
+    let period =
+        Period::starting_at_with_precision(start, duration, Precision::Milliseconds).unwrap();
+
+    assert_eq!(expected_start, period.start);
+    assert_eq!(Duration::milliseconds(101), period.duration);
+}
+
+#[test]
+fn that_period_with_millisecond_precision_can_not_be_created_with_sub_millisecond_duration() {
+    let result = Period::starting_at_with_precision(
+        Utc::now(),
+        Duration::microseconds(400),
+        Precision::Milliseconds,
+    );
+
+    assert!(matches!(result, Err(PeriodError::ZeroDurationError)));
+}
+
+#[test]
+fn that_adjust_duration_rounds_to_closest_millisecond() {
+    // 1.2345s -> 1.235s
+    let duration = Duration::microseconds(1_234_500);
+    let expected_result = Duration::milliseconds(1_235);
+
+    let result = adjust_duration(duration, Precision::Milliseconds);
+
+    assert_eq!(expected_result, result);
+}
+
+#[test]
+fn that_next_available_timestamp_with_millisecond_precision_returns_next_millisecond_occurrence() {
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let duration = Duration::milliseconds(250);
+    let now = timestamp + Duration::milliseconds(1_100);
+
+    let result =
+        next_available_timestamp(timestamp, &duration, now, Precision::Milliseconds).unwrap();
+
+    assert_eq!(timestamp + Duration::milliseconds(1_250), result);
+}
+
+#[test]
+fn that_relative_iterator_with_millisecond_precision_starts_after_the_clock() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let clock = MockClock::new(start + Duration::milliseconds(320));
+    let period = Period::starting_at_with_precision(
+        start,
+        Duration::milliseconds(100),
+        Precision::Milliseconds,
+    )
+    .unwrap()
+    .with_clock(clock);
+
+    let result: Vec<_> = period.upcoming_relative().take(3).collect();
+
+    assert_eq!(
+        vec![
+            start + Duration::milliseconds(400),
+            start + Duration::milliseconds(500),
+            start + Duration::milliseconds(600),
+        ],
+        result
+    );
+}
+
+#[test]
+fn that_first_timestamp_after_returns_next_millisecond_occurrence() {
+    let timestamp = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let after = timestamp + Duration::milliseconds(1_000);
+
+    let result = first_timestamp_after(timestamp, &Duration::milliseconds(250), after);
+
+    assert_eq!(timestamp + Duration::milliseconds(1_250), result);
+}
+
+#[test]
+fn that_period_with_count_ends_after_count_occurrences() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let duration = Duration::minutes(10);
+    let period = Period::starting_at(start, duration).unwrap().with_count(3);
+
+    let result: Vec<_> = period.upcoming_fixed().collect();
+
+    assert_eq!(vec![start, start + duration, start + duration * 2], result);
+}
+
+#[test]
+fn that_period_with_end_includes_occurrence_at_the_end() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2020, 1, 1, 0, 20, 0).unwrap();
+    let period = Period::starting_at(start, Duration::minutes(10))
+        .unwrap()
+        .with_end(end);
+
+    let result: Vec<_> = period.upcoming_fixed_owned().collect();
+
+    assert_eq!(3, result.len());
+    assert_eq!(Some(&end), result.last());
+}
+
+#[test]
+fn that_last_occurrence_is_the_earlier_of_end_and_count() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let period = Period::starting_at(start, Duration::minutes(10)).unwrap();
+
+    assert_eq!(None, period.last_occurrence());
+    assert_eq!(
+        Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 40, 0).unwrap()),
+        period.clone().with_count(5).last_occurrence()
+    );
+    assert_eq!(
+        Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 20, 0).unwrap()),
+        period
+            .with_count(5)
+            .with_end(Utc.with_ymd_and_hms(2020, 1, 1, 0, 25, 0).unwrap())
+            .last_occurrence()
+    );
+}
+
+#[test]
+fn that_relative_iterator_of_bounded_period_counts_occurrences_from_the_start() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let clock = MockClock::new(Utc.with_ymd_and_hms(2020, 1, 1, 0, 15, 0).unwrap());
+    let period = Period::starting_at(start, Duration::minutes(10))
+        .unwrap()
+        .with_count(4)
+        .with_clock(clock);
+
+    let result: Vec<_> = period.upcoming_relative_owned().collect();
+
+    assert_eq!(
+        vec![
+            Utc.with_ymd_and_hms(2020, 1, 1, 0, 20, 0).unwrap(),
+            Utc.with_ymd_and_hms(2020, 1, 1, 0, 30, 0).unwrap(),
+        ],
+        result
+    );
+}
+
+#[test]
+fn that_period_with_zero_count_has_no_occurrences() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let period = Period::starting_at(start, Duration::minutes(10))
+        .unwrap()
+        .with_count(0);
+
+    assert_eq!(0, period.upcoming_fixed().count());
+}
+
+#[test]
+fn that_count_beyond_the_range_of_dates_does_not_bound_the_period() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let period = Period::starting_at(start, Duration::days(365))
+        .unwrap()
+        .with_count(u32::MAX);
+
+    assert_eq!(None, period.last_occurrence());
+    assert_eq!(3, period.upcoming_fixed().take(3).count());
+}
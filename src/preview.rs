@@ -0,0 +1,190 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use chrono::{DateTime, Utc};
+
+use crate::zeitschaltuhr::TaskId;
+
+/// An upcoming occurrence of a task. The time is the scheduled time before jitter is applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpcomingRun {
+    pub task_id: TaskId,
+    /// The name of the task if it was given one.
+    pub name: Option<String>,
+    pub scheduled_at: DateTime<Utc>,
+}
+
+type Times = Box<dyn Iterator<Item = DateTime<Utc>> + Send>;
+
+/// Merges the times of several tasks in ascending order. Every source has to be sorted already.
+pub(crate) struct MergedRuns {
+    sources: Vec<(TaskId, Option<String>, Times)>,
+    heads: BinaryHeap<Reverse<(DateTime<Utc>, TaskId, usize)>>,
+}
+
+impl MergedRuns {
+    pub(crate) fn new(sources: Vec<(TaskId, Option<String>, Times)>) -> Self {
+        let mut merged = Self {
+            sources,
+            heads: BinaryHeap::new(),
+        };
+        for index in 0..merged.sources.len() {
+            merged.advance(index);
+        }
+        merged
+    }
+
+    fn advance(&mut self, index: usize) {
+        let (task_id, _, times) = &mut self.sources[index];
+        if let Some(time) = times.next() {
+            self.heads.push(Reverse((time, *task_id, index)));
+        }
+    }
+}
+
+impl Iterator for MergedRuns {
+    type Item = UpcomingRun;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((scheduled_at, task_id, index)) = self.heads.pop()?;
+        self.advance(index);
+        Some(UpcomingRun {
+            task_id,
+            name: self.sources[index].1.clone(),
+            scheduled_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::str::FromStr;
+
+    use chrono::{Duration, TimeZone};
+    use cron::Schedule;
+
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::period::Period;
+    use crate::task::{PrintingTask, TaskOptions};
+    use crate::temporal_iterator::TemporalIterator;
+    use crate::zeitschaltuhr::Zeitschaltuhr;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn minutes(minutes: i64) -> DateTime<Utc> {
+        start() + Duration::minutes(minutes)
+    }
+
+    fn task() -> Box<PrintingTask> {
+        Box::new(PrintingTask::new(String::new()))
+    }
+
+    fn period(minutes: i64) -> Box<Period> {
+        Box::new(Period::starting_at(start(), Duration::minutes(minutes)).unwrap())
+    }
+
+    #[test]
+    fn that_upcoming_runs_of_all_tasks_are_merged_in_time_order() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(MockClock::new(start()));
+        let hourly = zeitschaltuhr.add_task_with_options(
+            task(),
+            period(60),
+            TaskOptions::default().with_name("hourly"),
+        );
+        let other = zeitschaltuhr.add_task(task(), period(90));
+
+        let runs = zeitschaltuhr.upcoming_runs(4);
+
+        let expected = vec![
+            UpcomingRun {
+                task_id: hourly,
+                name: Some("hourly".to_string()),
+                scheduled_at: minutes(60),
+            },
+            UpcomingRun {
+                task_id: other,
+                name: None,
+                scheduled_at: minutes(90),
+            },
+            UpcomingRun {
+                task_id: hourly,
+                name: Some("hourly".to_string()),
+                scheduled_at: minutes(120),
+            },
+            UpcomingRun {
+                task_id: hourly,
+                name: Some("hourly".to_string()),
+                scheduled_at: minutes(180),
+            },
+        ];
+        assert_eq!(expected, runs);
+    }
+
+    #[test]
+    fn that_upcoming_runs_between_are_limited_to_the_range() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let schedule = Schedule::from_str("0 15 * * * * *").unwrap();
+        let cron = zeitschaltuhr.add_task(task(), Box::new(schedule));
+        let period = zeitschaltuhr.add_task(task(), period(30));
+
+        let runs = zeitschaltuhr.upcoming_runs_between(minutes(10), minutes(75));
+
+        let runs: Vec<_> = runs
+            .iter()
+            .map(|run| (run.task_id, run.scheduled_at))
+            .collect();
+        assert_eq!(
+            vec![
+                (cron, minutes(15)),
+                (period, minutes(30)),
+                (period, minutes(60)),
+            ],
+            runs
+        );
+    }
+
+    /// Fires every ten minutes from the start, regardless of the time it is asked for.
+    struct FromStart;
+
+    impl TemporalIterator for FromStart {
+        fn iter_times(&self) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+            Box::new((0..).map(|step| minutes(10 * step)))
+        }
+    }
+
+    #[test]
+    fn that_upcoming_runs_start_after_the_given_time() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let custom = zeitschaltuhr.add_task(task(), Box::new(FromStart));
+        zeitschaltuhr.add_task(task(), period(30));
+
+        let runs = zeitschaltuhr.upcoming_runs_between(minutes(30), minutes(50));
+
+        let runs: Vec<_> = runs
+            .iter()
+            .map(|run| (run.task_id, run.scheduled_at))
+            .collect();
+        assert_eq!(vec![(custom, minutes(40))], runs);
+    }
+
+    #[tokio::test]
+    async fn that_handle_previews_tasks_registered_at_runtime() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(MockClock::new(start()));
+        let removed = zeitschaltuhr.add_task(task(), period(10));
+        let handle = zeitschaltuhr.run();
+
+        let added = handle.add_task(task(), period(60)).unwrap();
+        handle.remove_task(removed).unwrap();
+        let runs = handle.upcoming_runs(2);
+
+        assert!(runs.iter().all(|run| run.task_id == added));
+        assert_eq!(minutes(60), runs[0].scheduled_at);
+        assert_eq!(minutes(120), runs[1].scheduled_at);
+    }
+}
@@ -0,0 +1,127 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// Describes how often and after which delay a failed execution is retried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Backoff,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Backoff {
+    Fixed(Duration),
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl RetryPolicy {
+    /// Retry with the same delay after every failed attempt.
+    /// `max_attempts` includes the first execution.
+    pub fn fixed(delay: Duration, max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            backoff: Backoff::Fixed(delay),
+        }
+    }
+
+    /// Retry with a delay which doubles after every failed attempt but does not exceed `max_delay`.
+    /// `max_attempts` includes the first execution.
+    pub fn exponential(initial_delay: Duration, max_delay: Duration, max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            backoff: Backoff::Exponential {
+                initial: initial_delay,
+                max: max_delay,
+            },
+        }
+    }
+
+    /// Delay before the attempt following the failed `attempt`. None if no attempts are left.
+    fn delay_after(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        Some(match &self.backoff {
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential { initial, max } => {
+                let factor = 2_i32.checked_pow(attempt - 1).unwrap_or(i32::MAX);
+                initial.checked_mul(factor).unwrap_or(*max).min(*max)
+            }
+        })
+    }
+
+    /// Delay before retrying the failed `attempt`. None if no attempts are left or if the retry
+    /// would not happen before the next regular occurrence of the task.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        now: DateTime<Utc>,
+        next_occurrence: Option<DateTime<Utc>>,
+    ) -> Option<Duration> {
+        self.delay_after(attempt).filter(|delay| {
+            next_occurrence.is_none_or(|next_occurrence| now + *delay < next_occurrence)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn that_fixed_policy_returns_same_delay_until_attempts_are_exhausted() {
+        let policy = RetryPolicy::fixed(Duration::seconds(5), 3);
+
+        assert_eq!(Some(Duration::seconds(5)), policy.delay_after(1));
+        assert_eq!(Some(Duration::seconds(5)), policy.delay_after(2));
+        assert_eq!(None, policy.delay_after(3));
+    }
+
+    #[test]
+    fn that_exponential_policy_doubles_delay_up_to_maximum() {
+        let policy = RetryPolicy::exponential(Duration::seconds(1), Duration::seconds(5), 10);
+
+        assert_eq!(Some(Duration::seconds(1)), policy.delay_after(1));
+        assert_eq!(Some(Duration::seconds(2)), policy.delay_after(2));
+        assert_eq!(Some(Duration::seconds(4)), policy.delay_after(3));
+        assert_eq!(Some(Duration::seconds(5)), policy.delay_after(4));
+        assert_eq!(Some(Duration::seconds(5)), policy.delay_after(9));
+        assert_eq!(None, policy.delay_after(10));
+    }
+
+    #[test]
+    fn that_exponential_policy_does_not_overflow() {
+        let policy = RetryPolicy::exponential(Duration::days(1), Duration::days(2), u32::MAX);
+
+        assert_eq!(Some(Duration::days(2)), policy.delay_after(100));
+    }
+
+    #[test]
+    fn that_policy_with_single_attempt_never_retries() {
+        let policy = RetryPolicy::fixed(Duration::seconds(1), 1);
+
+        assert_eq!(None, policy.delay_after(1));
+    }
+
+    #[test]
+    fn that_retry_delay_is_none_when_retry_would_collide_with_next_occurrence() {
+        let policy = RetryPolicy::fixed(Duration::seconds(30), 3);
+        let now = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            Some(Duration::seconds(30)),
+            policy.retry_delay(1, now, Some(now + Duration::seconds(31)))
+        );
+        assert_eq!(
+            None,
+            policy.retry_delay(1, now, Some(now + Duration::seconds(30)))
+        );
+        assert_eq!(
+            Some(Duration::seconds(30)),
+            policy.retry_delay(1, now, None)
+        );
+    }
+}
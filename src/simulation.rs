@@ -0,0 +1,317 @@
+use chrono::{DateTime, Utc};
+
+use crate::event::SkipReason;
+use crate::jitter::JitterSource;
+use crate::misfire::MisfireReason;
+use crate::task::TaskOptions;
+use crate::temporal_iterator::TemporalIterator;
+use crate::zeitschaltuhr::TaskId;
+
+/// An execution the Zeitschaltuhr would perform. Executions take the expected duration of their task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedExecution {
+    pub task_id: TaskId,
+    pub scheduled_at: DateTime<Utc>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// An occurrence the Zeitschaltuhr would not execute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedSkip {
+    pub task_id: TaskId,
+    pub scheduled_at: DateTime<Utc>,
+    pub skipped_at: DateTime<Utc>,
+    pub reason: SkipReason,
+}
+
+/// Result of simulating the tasks of a Zeitschaltuhr. Executions are ordered by their start and
+/// skipped occurrences by the time they were skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimulationReport {
+    pub executions: Vec<SimulatedExecution>,
+    pub skipped: Vec<SimulatedSkip>,
+}
+
+impl SimulationReport {
+    pub(crate) fn sort(&mut self) {
+        self.executions
+            .sort_by_key(|execution| (execution.started_at, execution.task_id));
+        self.skipped
+            .sort_by_key(|skip| (skip.skipped_at, skip.task_id));
+    }
+}
+
+/// Simulate the occurrences of a single task which are scheduled from `start` until before `end`.
+/// Follows the loop of a running task: occurrences queued behind a running execution delay the
+/// task, so that later occurrences become due at once and are subject to the misfire policy.
+pub(crate) fn simulate_task(
+    task_id: TaskId,
+    temporal_iterator: &dyn TemporalIterator,
+    options: &TaskOptions,
+    fallback_seed: u64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    report: &mut SimulationReport,
+) {
+    let mut jitter = options
+        .jitter
+        .as_ref()
+        .map(|jitter| JitterSource::new(jitter, fallback_seed));
+    let mut times = temporal_iterator
+        .iter_times_from(start)
+        .map(move |time| match jitter.as_mut() {
+            Some(jitter) => jitter.apply(time),
+            None => time,
+        })
+        .take_while(|time| *time < end)
+        .peekable();
+
+    let mut now = start;
+    let mut running = Vec::new();
+    while let Some(time) = times.next() {
+        now = now.max(time);
+        let mut due = vec![time];
+        while let Some(time) = times.next_if(|time| *time <= now) {
+            due.push(time);
+        }
+
+        let due = options
+            .misfire_policy
+            .resolve(due, options.max_lateness, now);
+        for (time, reason) in due.dropped {
+            report.skipped.push(SimulatedSkip {
+                task_id,
+                scheduled_at: time,
+                skipped_at: now,
+                reason: SkipReason::Misfire(reason),
+            });
+        }
+
+        for time in due.fire {
+            let Some(started_at) = options.overlap_policy.admit(now, &mut running) else {
+                report.skipped.push(SimulatedSkip {
+                    task_id,
+                    scheduled_at: time,
+                    skipped_at: now,
+                    reason: SkipReason::Overlapping,
+                });
+                continue;
+            };
+            // waiting for a queued execution delays the task
+            now = started_at;
+            // the occurrence may have become too late while it waited for its turn
+            let lateness = started_at - time;
+            if options
+                .max_lateness
+                .is_some_and(|max_lateness| lateness > max_lateness)
+            {
+                report.skipped.push(SimulatedSkip {
+                    task_id,
+                    scheduled_at: time,
+                    skipped_at: started_at,
+                    reason: SkipReason::Misfire(MisfireReason::TooLate(lateness)),
+                });
+                continue;
+            }
+            let finished_at = started_at + options.expected_duration;
+            running.push(finished_at);
+            report.executions.push(SimulatedExecution {
+                task_id,
+                scheduled_at: time,
+                started_at,
+                finished_at,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::jitter::Jitter;
+    use crate::misfire::MisfirePolicy;
+    use crate::overlap::OverlapPolicy;
+    use crate::period::Period;
+    use crate::task::PrintingTask;
+    use crate::zeitschaltuhr::Zeitschaltuhr;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn every_minute() -> Box<Period> {
+        Box::new(Period::starting_at(start(), Duration::minutes(1)).unwrap())
+    }
+
+    fn minutes(minutes: i64) -> DateTime<Utc> {
+        start() + Duration::minutes(minutes)
+    }
+
+    fn task() -> Box<PrintingTask> {
+        Box::new(PrintingTask::new(String::new()))
+    }
+
+    #[test]
+    fn that_executions_of_all_tasks_are_ordered_by_start() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let hourly = zeitschaltuhr.add_task(
+            task(),
+            Box::new(Period::starting_at(start(), Duration::hours(1)).unwrap()),
+        );
+        let every_90_minutes = zeitschaltuhr.add_task(
+            task(),
+            Box::new(Period::starting_at(start(), Duration::minutes(90)).unwrap()),
+        );
+
+        let report = zeitschaltuhr.simulate(start(), minutes(181));
+
+        let executions: Vec<_> = report
+            .executions
+            .iter()
+            .map(|execution| (execution.task_id, execution.started_at))
+            .collect();
+        assert_eq!(
+            vec![
+                (hourly, minutes(60)),
+                (every_90_minutes, minutes(90)),
+                (hourly, minutes(120)),
+                (hourly, minutes(180)),
+                (every_90_minutes, minutes(180)),
+            ],
+            executions
+        );
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn that_occurrences_during_execution_are_skipped_by_overlap_policy() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let options = TaskOptions::default()
+            .with_overlap_policy(OverlapPolicy::Skip)
+            .with_expected_duration(Duration::seconds(150));
+        zeitschaltuhr.add_task_with_options(task(), every_minute(), options);
+
+        let report = zeitschaltuhr.simulate(start(), minutes(6));
+
+        let started: Vec<_> = report
+            .executions
+            .iter()
+            .map(|execution| execution.started_at)
+            .collect();
+        let skipped: Vec<_> = report
+            .skipped
+            .iter()
+            .map(|skip| (skip.scheduled_at, skip.reason))
+            .collect();
+        assert_eq!(vec![minutes(1), minutes(4)], started);
+        assert_eq!(
+            vec![
+                (minutes(2), SkipReason::Overlapping),
+                (minutes(3), SkipReason::Overlapping),
+                (minutes(5), SkipReason::Overlapping),
+            ],
+            skipped
+        );
+    }
+
+    #[test]
+    fn that_queued_executions_delay_the_task_until_misfire_policy_applies() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let options = TaskOptions::default()
+            .with_overlap_policy(OverlapPolicy::Queue)
+            .with_misfire_policy(MisfirePolicy::FireOnce)
+            .with_expected_duration(Duration::seconds(150));
+        zeitschaltuhr.add_task_with_options(task(), every_minute(), options);
+
+        let report = zeitschaltuhr.simulate(start(), minutes(7));
+
+        let executions: Vec<_> = report
+            .executions
+            .iter()
+            .map(|execution| (execution.scheduled_at, execution.started_at))
+            .collect();
+        let skipped: Vec<_> = report
+            .skipped
+            .iter()
+            .map(|skip| (skip.scheduled_at, skip.skipped_at, skip.reason))
+            .collect();
+        let missed = SkipReason::Misfire(MisfireReason::Missed);
+        assert_eq!(
+            vec![
+                (minutes(1), minutes(1)),
+                (minutes(2), start() + Duration::seconds(210)),
+                (minutes(3), minutes(6)),
+                (minutes(6), start() + Duration::seconds(510)),
+            ],
+            executions
+        );
+        assert_eq!(
+            vec![
+                (minutes(4), minutes(6), missed),
+                (minutes(5), minutes(6), missed),
+            ],
+            skipped
+        );
+    }
+
+    #[test]
+    fn that_queued_occurrence_exceeding_max_lateness_is_skipped() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let options = TaskOptions::default()
+            .with_overlap_policy(OverlapPolicy::Queue)
+            .with_max_lateness(Duration::seconds(60))
+            .with_expected_duration(Duration::seconds(150));
+        zeitschaltuhr.add_task_with_options(task(), every_minute(), options);
+
+        let report = zeitschaltuhr.simulate(start(), minutes(4));
+
+        let executions: Vec<_> = report
+            .executions
+            .iter()
+            .map(|execution| (execution.scheduled_at, execution.started_at))
+            .collect();
+        let skipped: Vec<_> = report
+            .skipped
+            .iter()
+            .map(|skip| (skip.scheduled_at, skip.skipped_at, skip.reason))
+            .collect();
+        assert_eq!(
+            vec![
+                (minutes(1), minutes(1)),
+                (minutes(3), start() + Duration::seconds(210)),
+            ],
+            executions
+        );
+        let too_late = SkipReason::Misfire(MisfireReason::TooLate(Duration::seconds(90)));
+        assert_eq!(
+            vec![(minutes(2), start() + Duration::seconds(210), too_late)],
+            skipped
+        );
+    }
+
+    #[test]
+    fn that_seeded_jitter_is_applied_reproducibly() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let options = TaskOptions::default().with_jitter(Jitter::seeded(Duration::seconds(30), 42));
+        zeitschaltuhr.add_task_with_options(task(), every_minute(), options);
+
+        let first = zeitschaltuhr.simulate(start(), minutes(10));
+        let second = zeitschaltuhr.simulate(start(), minutes(10));
+
+        assert_eq!(first, second);
+        assert_eq!(9, first.executions.len());
+        for (execution, minute) in first.executions.iter().zip(1..) {
+            let offset = execution.scheduled_at - minutes(minute);
+            assert!(offset >= Duration::zero() && offset <= Duration::seconds(30));
+            assert_eq!(execution.scheduled_at, execution.started_at);
+        }
+        assert!(first
+            .executions
+            .iter()
+            .any(|execution| execution.scheduled_at.timestamp() % 60 != 0));
+    }
+}
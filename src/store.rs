@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "json")]
+pub use json::JsonFileStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+pub type StoreError = Box<dyn Error + Send + Sync>;
+
+pub(crate) type StoreErrorHandler = Arc<dyn Fn(&StoreError) + Send + Sync>;
+
+/// Failed writes are traced if tracing is enabled and printed to stderr otherwise.
+pub(crate) fn default_store_error_handler() -> StoreErrorHandler {
+    Arc::new(|error: &StoreError| {
+        #[cfg(feature = "tracing")]
+        tracing::error!(%error, "failed to save the checkpoints");
+        #[cfg(not(feature = "tracing"))]
+        eprintln!("failed to save the checkpoints: {error}");
+    })
+}
+
+/// Progress of a task which is kept across restarts of the Zeitschaltuhr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaskCheckpoint {
+    /// The latest scheduled time at which the task fired.
+    pub last_fired: Option<DateTime<Utc>>,
+    /// The time at which the latest successful execution finished.
+    pub last_completed: Option<DateTime<Utc>>,
+}
+
+/// Storage for the checkpoints of tasks. Checkpoints are identified by the name of their task.
+pub trait StateStore: Send + Sync {
+    /// Load the checkpoints of all tasks.
+    fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError>;
+
+    /// Save the checkpoint of a single task, replacing the previous one.
+    fn save(&self, name: &str, checkpoint: &TaskCheckpoint) -> Result<(), StoreError>;
+
+    /// Save the checkpoints of several tasks at once. Defaults to saving them one by one.
+    fn save_all(&self, checkpoints: &HashMap<String, TaskCheckpoint>) -> Result<(), StoreError> {
+        for (name, checkpoint) in checkpoints {
+            self.save(name, checkpoint)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: StateStore + ?Sized> StateStore for Arc<S> {
+    fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+        (**self).load()
+    }
+
+    fn save(&self, name: &str, checkpoint: &TaskCheckpoint) -> Result<(), StoreError> {
+        (**self).save(name, checkpoint)
+    }
+
+    fn save_all(&self, checkpoints: &HashMap<String, TaskCheckpoint>) -> Result<(), StoreError> {
+        (**self).save_all(checkpoints)
+    }
+}
+
+/// Keeps checkpoints in memory only. Useful for tests and for sharing checkpoints between
+/// Zeitschaltuhren within a single process.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    checkpoints: Mutex<HashMap<String, TaskCheckpoint>>,
+}
+
+impl StateStore for MemoryStore {
+    fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+        Ok(self.checkpoints.lock().unwrap().clone())
+    }
+
+    fn save(&self, name: &str, checkpoint: &TaskCheckpoint) -> Result<(), StoreError> {
+        self.checkpoints
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), *checkpoint);
+        Ok(())
+    }
+}
+
+/// Writes checkpoints to the store on the blocking thread pool, so a slow store does not hold up
+/// the scheduling of tasks. Checkpoints which are saved while a write is in progress are written
+/// together afterwards, keeping only the latest checkpoint of every task. Checkpoints of a failed
+/// write are queued again and retried with the next save.
+pub(crate) struct CheckpointWriter {
+    store: Arc<dyn StateStore>,
+    error_handler: Mutex<StoreErrorHandler>,
+    pending: Mutex<HashMap<String, TaskCheckpoint>>,
+    writing: watch::Sender<bool>,
+}
+
+impl CheckpointWriter {
+    pub(crate) fn new(store: Arc<dyn StateStore>, error_handler: StoreErrorHandler) -> Self {
+        Self {
+            store,
+            error_handler: Mutex::new(error_handler),
+            pending: Mutex::new(HashMap::new()),
+            writing: watch::channel(false).0,
+        }
+    }
+
+    /// Queue the checkpoint and start writing unless a write is in progress already.
+    fn save(self: &Arc<Self>, name: &str, checkpoint: TaskCheckpoint) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.insert(name.to_string(), checkpoint);
+        if !self.writing.send_replace(true) {
+            let writer = self.clone();
+            tokio::task::spawn_blocking(move || writer.write_pending());
+        }
+    }
+
+    fn write_pending(&self) {
+        loop {
+            let checkpoints = {
+                let mut pending = self.pending.lock().unwrap();
+                if pending.is_empty() {
+                    self.writing.send_replace(false);
+                    return;
+                }
+                std::mem::take(&mut *pending)
+            };
+            if let Err(error) = self.store.save_all(&checkpoints) {
+                let error_handler = self.error_handler.lock().unwrap().clone();
+                error_handler(&error);
+                // checkpoints saved in the meantime are newer than the failed ones
+                let mut pending = self.pending.lock().unwrap();
+                for (name, checkpoint) in checkpoints {
+                    pending.entry(name).or_insert(checkpoint);
+                }
+                // retrying right away would likely fail again
+                self.writing.send_replace(false);
+                return;
+            }
+        }
+    }
+
+    pub(crate) fn set_error_handler(&self, error_handler: StoreErrorHandler) {
+        *self.error_handler.lock().unwrap() = error_handler;
+    }
+
+    /// Wait until every queued checkpoint is written.
+    pub(crate) async fn flush(&self) {
+        let _ = self.writing.subscribe().wait_for(|writing| !*writing).await;
+    }
+}
+
+/// Keeps the checkpoint of a running task up to date in the store.
+pub(crate) struct Checkpointer {
+    name: String,
+    writer: Arc<CheckpointWriter>,
+    checkpoint: Mutex<TaskCheckpoint>,
+}
+
+impl Checkpointer {
+    pub(crate) fn new(
+        name: String,
+        writer: Arc<CheckpointWriter>,
+        checkpoint: TaskCheckpoint,
+    ) -> Self {
+        Self {
+            name,
+            writer,
+            checkpoint: Mutex::new(checkpoint),
+        }
+    }
+
+    pub(crate) fn checkpoint(&self) -> TaskCheckpoint {
+        *self.checkpoint.lock().unwrap()
+    }
+
+    pub(crate) fn fired(&self, scheduled_at: DateTime<Utc>) {
+        let mut checkpoint = self.checkpoint.lock().unwrap();
+        checkpoint.last_fired = checkpoint.last_fired.max(Some(scheduled_at));
+        self.save(&checkpoint);
+    }
+
+    pub(crate) fn completed(&self, finished_at: DateTime<Utc>) {
+        let mut checkpoint = self.checkpoint.lock().unwrap();
+        checkpoint.last_completed = checkpoint.last_completed.max(Some(finished_at));
+        self.save(&checkpoint);
+    }
+
+    fn save(&self, checkpoint: &TaskCheckpoint) {
+        self.writer.save(&self.name, *checkpoint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn that_checkpointer_saves_latest_times() {
+        let store = Arc::new(MemoryStore::default());
+        let writer = Arc::new(CheckpointWriter::new(
+            store.clone(),
+            default_store_error_handler(),
+        ));
+        let checkpointer = Checkpointer::new(
+            "backup".to_string(),
+            writer.clone(),
+            TaskCheckpoint::default(),
+        );
+        let first = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let second = Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap();
+
+        checkpointer.fired(second);
+        checkpointer.fired(first);
+        checkpointer.completed(first);
+        writer.flush().await;
+
+        let expected = TaskCheckpoint {
+            last_fired: Some(second),
+            last_completed: Some(first),
+        };
+        assert_eq!(checkpointer.checkpoint(), expected);
+        assert_eq!(store.load().unwrap().get("backup"), Some(&expected));
+    }
+
+    /// Takes a while for every write and counts them.
+    #[derive(Default)]
+    struct SlowStore {
+        store: MemoryStore,
+        writes: AtomicUsize,
+    }
+
+    impl StateStore for SlowStore {
+        fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+            self.store.load()
+        }
+
+        fn save(&self, name: &str, checkpoint: &TaskCheckpoint) -> Result<(), StoreError> {
+            self.store.save(name, checkpoint)
+        }
+
+        fn save_all(
+            &self,
+            checkpoints: &HashMap<String, TaskCheckpoint>,
+        ) -> Result<(), StoreError> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            self.store.save_all(checkpoints)
+        }
+    }
+
+    #[tokio::test]
+    async fn that_checkpoints_saved_during_a_write_are_written_together() {
+        let store = Arc::new(SlowStore::default());
+        let writer = Arc::new(CheckpointWriter::new(
+            store.clone(),
+            default_store_error_handler(),
+        ));
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+
+        for minute in 0..100 {
+            let name = format!("task-{}", minute % 10);
+            let checkpoint = TaskCheckpoint {
+                last_fired: Some(start + chrono::Duration::minutes(minute)),
+                last_completed: None,
+            };
+            writer.save(&name, checkpoint);
+        }
+        writer.flush().await;
+
+        assert!(store.writes.load(Ordering::SeqCst) <= 2);
+        let checkpoints = store.load().unwrap();
+        assert_eq!(10, checkpoints.len());
+        assert_eq!(
+            Some(start + chrono::Duration::minutes(99)),
+            checkpoints["task-9"].last_fired
+        );
+    }
+
+    /// Fails every write until it is repaired.
+    #[derive(Default)]
+    struct BrokenStore {
+        store: MemoryStore,
+        repaired: std::sync::atomic::AtomicBool,
+    }
+
+    impl StateStore for BrokenStore {
+        fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+            self.store.load()
+        }
+
+        fn save(&self, name: &str, checkpoint: &TaskCheckpoint) -> Result<(), StoreError> {
+            if !self.repaired.load(Ordering::SeqCst) {
+                return Err("store is broken".into());
+            }
+            self.store.save(name, checkpoint)
+        }
+    }
+
+    #[tokio::test]
+    async fn that_failed_checkpoints_are_reported_and_retried_with_the_next_save() {
+        let store = Arc::new(BrokenStore::default());
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let reported = errors.clone();
+        let writer = Arc::new(CheckpointWriter::new(
+            store.clone(),
+            Arc::new(move |error: &StoreError| reported.lock().unwrap().push(error.to_string())),
+        ));
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let fired = |minutes| TaskCheckpoint {
+            last_fired: Some(start + chrono::Duration::minutes(minutes)),
+            last_completed: None,
+        };
+
+        writer.save("backup", fired(0));
+        writer.flush().await;
+        assert_eq!(vec!["store is broken"], *errors.lock().unwrap());
+        assert!(store.load().unwrap().is_empty());
+
+        store.repaired.store(true, Ordering::SeqCst);
+        writer.save("cleanup", fired(1));
+        writer.flush().await;
+
+        let checkpoints = store.load().unwrap();
+        assert_eq!(Some(&fired(0)), checkpoints.get("backup"));
+        assert_eq!(Some(&fired(1)), checkpoints.get("cleanup"));
+        assert_eq!(1, errors.lock().unwrap().len());
+    }
+}
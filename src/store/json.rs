@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::store::{StateStore, StoreError, TaskCheckpoint};
+
+/// Keeps the checkpoints of all tasks in a single JSON file. Every save writes a temporary file,
+/// flushes it to disk and renames it over the previous file, so a crash never leaves a partially
+/// written file behind.
+pub struct JsonFileStore {
+    path: PathBuf,
+    checkpoints: Mutex<Option<HashMap<String, TaskCheckpoint>>>,
+}
+
+impl JsonFileStore {
+    /// Create a store for the file at `path`. A missing file is treated as an empty store.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            checkpoints: Mutex::new(None),
+        }
+    }
+
+    fn read(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+impl StateStore for JsonFileStore {
+    fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+        let checkpoints = self.read()?;
+        *self.checkpoints.lock().unwrap() = Some(checkpoints.clone());
+        Ok(checkpoints)
+    }
+
+    fn save(&self, name: &str, checkpoint: &TaskCheckpoint) -> Result<(), StoreError> {
+        self.save_all(&HashMap::from([(name.to_string(), *checkpoint)]))
+    }
+
+    fn save_all(&self, checkpoints: &HashMap<String, TaskCheckpoint>) -> Result<(), StoreError> {
+        let mut stored = self.checkpoints.lock().unwrap();
+        // keep the checkpoints of other tasks even if the store was never loaded
+        if stored.is_none() {
+            *stored = Some(self.read()?);
+        }
+        let stored = stored.as_mut().unwrap();
+        stored.extend(
+            checkpoints
+                .iter()
+                .map(|(name, checkpoint)| (name.clone(), *checkpoint)),
+        );
+
+        let temporary_path = self.path.with_extension("tmp");
+        let mut file = File::create(&temporary_path)?;
+        file.write_all(serde_json::to_string_pretty(stored)?.as_bytes())?;
+        // the content has to be on disk before the file replaces the previous one
+        file.sync_all()?;
+        fs::rename(&temporary_path, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn temporary_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "zeitschaltuhr-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn that_missing_file_is_loaded_as_empty_store() {
+        let store = JsonFileStore::new(temporary_file("missing"));
+
+        let checkpoints = store.load().unwrap();
+
+        assert!(checkpoints.is_empty());
+    }
+
+    #[test]
+    fn that_saved_checkpoints_are_loaded_by_a_new_store() {
+        let path = temporary_file("roundtrip");
+        let backup = TaskCheckpoint {
+            last_fired: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).single(),
+            last_completed: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 5).single(),
+        };
+        let vacuum = TaskCheckpoint {
+            last_fired: Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).single(),
+            last_completed: None,
+        };
+
+        JsonFileStore::new(&path).save("backup", &backup).unwrap();
+        JsonFileStore::new(&path).save("vacuum", &vacuum).unwrap();
+        let checkpoints = JsonFileStore::new(&path).load().unwrap();
+
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints["backup"], backup);
+        assert_eq!(checkpoints["vacuum"], vacuum);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+
+use crate::store::{StateStore, StoreError, TaskCheckpoint};
+
+const UPSERT: &str =
+    "INSERT INTO task_checkpoints (name, last_fired, last_completed) VALUES (?1, ?2, ?3)
+    ON CONFLICT (name) DO UPDATE SET
+        last_fired = excluded.last_fired,
+        last_completed = excluded.last_completed";
+
+/// Keeps the checkpoints of all tasks in an embedded SQLite database.
+pub struct SqliteStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Open the database at `path`. The database and its table are created if they do not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open a database which only lives in memory.
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, StoreError> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS task_checkpoints (
+                name TEXT PRIMARY KEY,
+                last_fired TEXT,
+                last_completed TEXT
+            )",
+            [],
+        )?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+impl StateStore for SqliteStore {
+    fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement =
+            connection.prepare("SELECT name, last_fired, last_completed FROM task_checkpoints")?;
+        let checkpoints = statement
+            .query_map([], |row| {
+                let checkpoint = TaskCheckpoint {
+                    last_fired: row.get(1)?,
+                    last_completed: row.get(2)?,
+                };
+                Ok((row.get(0)?, checkpoint))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(checkpoints)
+    }
+
+    fn save(&self, name: &str, checkpoint: &TaskCheckpoint) -> Result<(), StoreError> {
+        self.connection.lock().unwrap().execute(
+            UPSERT,
+            params![name, checkpoint.last_fired, checkpoint.last_completed],
+        )?;
+        Ok(())
+    }
+
+    fn save_all(&self, checkpoints: &HashMap<String, TaskCheckpoint>) -> Result<(), StoreError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(UPSERT)?;
+            for (name, checkpoint) in checkpoints {
+                statement.execute(params![
+                    name,
+                    checkpoint.last_fired,
+                    checkpoint.last_completed
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn that_saved_checkpoints_are_loaded() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let first = TaskCheckpoint {
+            last_fired: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).single(),
+            last_completed: None,
+        };
+        let second = TaskCheckpoint {
+            last_fired: Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).single(),
+            last_completed: Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 5).single(),
+        };
+
+        store.save("backup", &first).unwrap();
+        store.save("backup", &second).unwrap();
+        let checkpoints = store.load().unwrap();
+
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints["backup"], second);
+    }
+
+    #[test]
+    fn that_checkpoints_are_saved_together() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let checkpoint = TaskCheckpoint {
+            last_fired: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).single(),
+            last_completed: None,
+        };
+        let checkpoints = HashMap::from([
+            ("backup".to_string(), checkpoint),
+            ("vacuum".to_string(), checkpoint),
+        ]);
+
+        store.save_all(&checkpoints).unwrap();
+
+        assert_eq!(store.load().unwrap(), checkpoints);
+    }
+}
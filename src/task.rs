@@ -1,5 +1,136 @@
-pub trait Task: Send {
-    fn execute(&self);
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::exclusion::ExclusionGroup;
+use crate::jitter::Jitter;
+use crate::misfire::MisfirePolicy;
+use crate::overlap::OverlapPolicy;
+use crate::retry::RetryPolicy;
+use tokio::task::JoinHandle;
+
+pub type TaskError = Box<dyn Error + Send + Sync>;
+
+/// Result of a single execution. Errors are passed to the error handler of the Zeitschaltuhr.
+pub type TaskResult = Result<(), TaskError>;
+
+pub trait Task: Send + Sync {
+    fn execute(&self) -> TaskResult;
+}
+
+/// A task whose execution is awaited by the Zeitschaltuhr, e.g. to call asynchronous clients.
+pub trait AsyncTask: Send + Sync {
+    fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>>;
+}
+
+/// An execution took longer than its timeout.
+#[derive(Debug, PartialEq)]
+pub enum TimeoutError {
+    /// The execution of an asynchronous task was abandoned.
+    Abandoned(Duration),
+    /// The execution of a synchronous task can not be stopped and keeps running in the background.
+    /// Its exclusion group and its slots are only handed on once it has returned.
+    Overdue(Duration),
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutError::Abandoned(timeout) => {
+                write!(f, "execution abandoned after timeout of {timeout:?}")
+            }
+            TimeoutError::Overdue(timeout) => {
+                write!(f, "execution still running after timeout of {timeout:?}")
+            }
+        }
+    }
+}
+
+impl Error for TimeoutError {}
+
+/// Options which control how the Zeitschaltuhr executes a single task.
+#[derive(Debug, Clone, Default)]
+pub struct TaskOptions {
+    pub(crate) name: Option<String>,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) overlap_policy: OverlapPolicy,
+    pub(crate) misfire_policy: MisfirePolicy,
+    pub(crate) max_lateness: Option<chrono::Duration>,
+    pub(crate) priority: i32,
+    pub(crate) exclusion_group: Option<ExclusionGroup>,
+    pub(crate) jitter: Option<Jitter>,
+    pub(crate) expected_duration: chrono::Duration,
+}
+
+impl TaskOptions {
+    /// Name the task in logs and traces. Defaults to the id of the task.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Retry failed executions according to the policy.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Limit the duration of a single execution. Overrides the default timeout of the Zeitschaltuhr.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Decide what happens with occurrences which become due while the task is still running.
+    /// Defaults to [`OverlapPolicy::Queue`].
+    pub fn with_overlap_policy(mut self, overlap_policy: OverlapPolicy) -> Self {
+        self.overlap_policy = overlap_policy;
+        self
+    }
+
+    /// Decide what happens when several occurrences are due at once. Defaults to [`MisfirePolicy::FireAll`].
+    pub fn with_misfire_policy(mut self, misfire_policy: MisfirePolicy) -> Self {
+        self.misfire_policy = misfire_policy;
+        self
+    }
+
+    /// Drop occurrences which could not be started within `max_lateness` after their scheduled time.
+    pub fn with_max_lateness(mut self, max_lateness: chrono::Duration) -> Self {
+        self.max_lateness = Some(max_lateness);
+        self
+    }
+
+    /// Executions with a higher priority are started first when the Zeitschaltuhr limits the
+    /// number of concurrent executions. Defaults to 0.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Never execute the task at the same time as other tasks of the group.
+    pub fn with_exclusion_group(mut self, exclusion_group: ExclusionGroup) -> Self {
+        self.exclusion_group = Some(exclusion_group);
+        self
+    }
+
+    /// Shift every occurrence of the task by an offset within the window of the jitter.
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    /// Assume that executions take `expected_duration` when the schedule is simulated. Defaults to zero.
+    pub fn with_expected_duration(mut self, expected_duration: chrono::Duration) -> Self {
+        self.expected_duration = expected_duration;
+        self
+    }
 }
 
 pub struct PrintingTask(String);
@@ -11,7 +142,201 @@ impl PrintingTask {
 }
 
 impl Task for PrintingTask {
-    fn execute(&self) {
+    fn execute(&self) -> TaskResult {
         println!("{}", self.0);
+        Ok(())
+    }
+}
+
+/// Either kind of task the Zeitschaltuhr is able to execute.
+#[derive(Clone)]
+pub(crate) enum TaskKind {
+    Sync(Arc<dyn Task>),
+    Async(Arc<dyn AsyncTask>),
+}
+
+impl TaskKind {
+    /// Execute the task. An execution which exceeds the timeout is reported as a `TimeoutError`.
+    pub(crate) async fn execute(&self, timeout: Option<Duration>) -> Execution {
+        match self {
+            TaskKind::Sync(task) => {
+                // run the task on the blocking thread pool so it does not stall the runtime
+                let task = task.clone();
+                #[cfg(feature = "tracing")]
+                let span = tracing::Span::current();
+                let mut execution = tokio::task::spawn_blocking(move || {
+                    #[cfg(feature = "tracing")]
+                    let _entered = span.enter();
+                    task.execute()
+                });
+                let result = match timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, &mut execution).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            return Execution {
+                                result: Err(TimeoutError::Overdue(timeout).into()),
+                                overdue: Some(execution),
+                            }
+                        }
+                    },
+                    None => execution.await,
+                };
+                Execution::finished(result.unwrap_or_else(|join_error| Err(join_error.into())))
+            }
+            TaskKind::Async(task) => {
+                let execution = CatchUnwind(task.execute());
+                Execution::finished(match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, execution)
+                        .await
+                        .unwrap_or_else(|_| Err(TimeoutError::Abandoned(timeout).into())),
+                    None => execution.await,
+                })
+            }
+        }
+    }
+}
+
+/// Result of executing a task up to its timeout.
+pub(crate) struct Execution {
+    pub(crate) result: TaskResult,
+    /// A synchronous task which exceeded its timeout and is still running on the blocking thread pool.
+    pub(crate) overdue: Option<JoinHandle<TaskResult>>,
+}
+
+impl Execution {
+    fn finished(result: TaskResult) -> Self {
+        Self {
+            result,
+            overdue: None,
+        }
+    }
+}
+
+/// Reports a panic of an asynchronous task as an error, like the blocking thread pool does for
+/// synchronous tasks.
+struct CatchUnwind<F>(F);
+
+impl<F: Future<Output = TaskResult> + Unpin> Future for CatchUnwind<F> {
+    type Output = TaskResult;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = &mut self.0;
+        match panic::catch_unwind(AssertUnwindSafe(|| Pin::new(future).poll(cx))) {
+            Ok(poll) => poll,
+            Err(payload) => {
+                let message = panic_message(&*payload);
+                Poll::Ready(Err(format!("task panicked with message {message:?}").into()))
+            }
+        }
+    }
+}
+
+/// The message a panic was started with, which is empty if it is neither a `&str` nor a `String`.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+impl From<Box<dyn Task>> for TaskKind {
+    fn from(task: Box<dyn Task>) -> Self {
+        TaskKind::Sync(Arc::from(task))
+    }
+}
+
+impl From<Box<dyn AsyncTask>> for TaskKind {
+    fn from(task: Box<dyn AsyncTask>) -> Self {
+        TaskKind::Async(Arc::from(task))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct SleepingTask(Duration);
+
+    impl Task for SleepingTask {
+        fn execute(&self) -> TaskResult {
+            std::thread::sleep(self.0);
+            Ok(())
+        }
+    }
+
+    impl AsyncTask for SleepingTask {
+        fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>> {
+            Box::pin(async move {
+                tokio::time::sleep(self.0).await;
+                Ok(())
+            })
+        }
+    }
+
+    fn timeout_error(result: TaskResult) -> TimeoutError {
+        *result.unwrap_err().downcast::<TimeoutError>().unwrap()
+    }
+
+    #[tokio::test]
+    async fn that_sync_task_exceeding_timeout_is_reported_as_overdue_and_keeps_running() {
+        let task: Box<dyn Task> = Box::new(SleepingTask(Duration::from_millis(300)));
+        let timeout = Duration::from_millis(20);
+
+        let execution = TaskKind::from(task).execute(Some(timeout)).await;
+
+        assert_eq!(
+            TimeoutError::Overdue(timeout),
+            timeout_error(execution.result)
+        );
+        let overdue = execution.overdue.unwrap();
+        assert!(!overdue.is_finished());
+        assert!(overdue.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn that_async_task_exceeding_timeout_is_abandoned() {
+        let task: Box<dyn AsyncTask> = Box::new(SleepingTask(Duration::from_secs(10)));
+        let timeout = Duration::from_millis(20);
+
+        let execution = TaskKind::from(task).execute(Some(timeout)).await;
+
+        assert_eq!(
+            TimeoutError::Abandoned(timeout),
+            timeout_error(execution.result)
+        );
+        assert!(execution.overdue.is_none());
+    }
+
+    struct PanickingTask;
+
+    impl AsyncTask for PanickingTask {
+        fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>> {
+            Box::pin(async move { panic!("task panicked") })
+        }
+    }
+
+    #[tokio::test]
+    async fn that_panicking_async_task_is_reported_as_error() {
+        let task: Box<dyn AsyncTask> = Box::new(PanickingTask);
+
+        let execution = TaskKind::from(task).execute(None).await;
+
+        assert_eq!(
+            "task panicked with message \"task panicked\"",
+            execution.result.unwrap_err().to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn that_task_within_timeout_succeeds() {
+        let task: Box<dyn AsyncTask> = Box::new(SleepingTask(Duration::from_millis(1)));
+
+        let execution = TaskKind::from(task)
+            .execute(Some(Duration::from_secs(1)))
+            .await;
+
+        assert!(execution.result.is_ok());
     }
 }
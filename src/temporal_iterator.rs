@@ -1,30 +1,100 @@
+use crate::calendar::CalendarPeriod;
 use crate::period::Period;
 use chrono::{DateTime, Utc};
 use cron::Schedule;
 
 pub trait TemporalIterator: Send + Sync + 'static {
     fn iter_times(&self) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send>;
+
+    /// Iterate over the times which are upcoming at `now`. Used to follow the clock of the
+    /// Zeitschaltuhr instead of the system time. Defaults to `iter_times`.
+    fn iter_times_from(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        let _ = now;
+        self.iter_times()
+    }
+
+    /// Iterate over the times after `after`, which may lie in the past. Used to resume a schedule
+    /// from a previous run.
+    fn iter_times_after(
+        &self,
+        after: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.iter_times().skip_while(move |time| *time <= after))
+    }
 }
 
 impl TemporalIterator for Period {
     fn iter_times(&self) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
         Box::new(self.clone().upcoming_relative_owned())
     }
+
+    fn iter_times_from(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.clone().upcoming_relative_owned_at(now))
+    }
+
+    fn iter_times_after(
+        &self,
+        after: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.clone().upcoming_after_owned(after))
+    }
+}
+
+impl TemporalIterator for CalendarPeriod {
+    fn iter_times(&self) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.clone().upcoming_relative_owned())
+    }
+
+    fn iter_times_from(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.clone().upcoming_after_owned(now))
+    }
+
+    fn iter_times_after(
+        &self,
+        after: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.clone().upcoming_after_owned(after))
+    }
 }
 
 impl TemporalIterator for Schedule {
     fn iter_times(&self) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
         Box::new(self.upcoming_owned(Utc))
     }
+
+    fn iter_times_from(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.after_owned(now))
+    }
+
+    fn iter_times_after(
+        &self,
+        after: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+        Box::new(self.after_owned(after))
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use chrono::{Duration, TimeZone, Utc};
+    use chrono_tz::Europe::Berlin;
     use std::str::FromStr;
 
     use super::*;
+    use crate::calendar::CalendarUnit;
 
     #[test]
     fn that_iter_times_of_cron_schedule_returns_iterator_of_datetimes() {
@@ -60,4 +130,55 @@ mod tests {
 
         assert_eq!(second.unwrap(), first.unwrap() + duration);
     }
+
+    #[test]
+    fn that_iter_times_after_of_cron_schedule_starts_after_given_time() {
+        let expression = "0   30   12     *       May  *  2100";
+        let schedule = Schedule::from_str(expression).unwrap();
+        let after = Utc.with_ymd_and_hms(2100, 5, 30, 12, 30, 0).unwrap();
+
+        let mut dates = schedule.iter_times_after(after);
+
+        assert_eq!(
+            dates.next(),
+            Utc.with_ymd_and_hms(2100, 5, 31, 12, 30, 0).single()
+        );
+        assert_eq!(dates.next(), None);
+    }
+
+    #[test]
+    fn that_iter_times_after_of_period_can_return_times_in_the_past() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let period = Period::starting_at(start, Duration::minutes(10)).unwrap();
+        let after = Utc.with_ymd_and_hms(2020, 1, 1, 0, 20, 0).unwrap();
+
+        let mut dates = period.iter_times_after(after);
+
+        assert_eq!(
+            dates.next(),
+            Utc.with_ymd_and_hms(2020, 1, 1, 0, 30, 0).single()
+        );
+        assert_eq!(
+            dates.next(),
+            Utc.with_ymd_and_hms(2020, 1, 1, 0, 40, 0).single()
+        );
+    }
+
+    #[test]
+    fn that_iter_times_from_of_calendar_period_keeps_local_time_of_day() {
+        let start = Berlin.with_ymd_and_hms(2020, 3, 1, 7, 0, 0).unwrap();
+        let period = CalendarPeriod::starting_at(start, 1, CalendarUnit::Days).unwrap();
+        let now = Utc.with_ymd_and_hms(2020, 3, 28, 12, 0, 0).unwrap();
+
+        let mut dates = period.iter_times_from(now);
+
+        assert_eq!(
+            dates.next(),
+            Utc.with_ymd_and_hms(2020, 3, 29, 5, 0, 0).single()
+        );
+        assert_eq!(
+            dates.next(),
+            Utc.with_ymd_and_hms(2020, 3, 30, 5, 0, 0).single()
+        );
+    }
 }
@@ -1,67 +1,1357 @@
-use std::time::{Instant, SystemTime};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt;
+use std::iter::Peekable;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, Weak};
 
-use crate::task::Task;
+use crate::clock::{Clock, SystemClock};
+use crate::event::{Event, EventListener, SkipReason};
+use crate::exclusion::ExclusionGroups;
+use crate::history::{ExecutionHistory, ExecutionOutcome, ExecutionRecord};
+use crate::jitter::JitterSource;
+use crate::limiter::ExecutionLimiter;
+use crate::misfire::MisfireReason;
+use crate::overlap::OverlapGuard;
+use crate::preview::{MergedRuns, UpcomingRun};
+use crate::simulation::{simulate_task, SimulationReport};
+use crate::store::{
+    default_store_error_handler, CheckpointWriter, Checkpointer, StateStore, StoreError,
+    StoreErrorHandler, TaskCheckpoint,
+};
+use crate::task::{panic_message, AsyncTask, Execution, Task, TaskError, TaskKind, TaskOptions};
 use crate::temporal_iterator::TemporalIterator;
 use chrono::DateTime;
-use tokio::time::sleep_until;
+use tokio::sync::{mpsc, watch, OwnedSemaphorePermit};
+use tokio::task::{AbortHandle, JoinHandle, JoinSet};
 
 use chrono::Utc;
 
+/// Identifies a task registered at a Zeitschaltuhr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaskId(u64);
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task-{}", self.0)
+    }
+}
+
+#[cfg(test)]
+impl TaskId {
+    pub(crate) fn new(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZeitschaltuhrError {
+    UnknownTaskError(TaskId),
+    ShutdownTimeoutError,
+    ShutDownError,
+    /// A duration which has to be positive is zero.
+    ZeroDurationError,
+    /// The tasks stopped being driven, because the driver panicked with the given message.
+    DriverPanicError(String),
+}
+
+/// A failed execution of a task.
+#[derive(Debug)]
+pub struct TaskFailure {
+    pub task_id: TaskId,
+    pub scheduled_at: DateTime<Utc>,
+    /// Number of the failed attempt, starting with 1 for the first execution of an occurrence.
+    pub attempt: u32,
+    pub error: TaskError,
+}
+
+/// An occurrence of a task which was dropped without being executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Misfire {
+    pub task_id: TaskId,
+    pub scheduled_at: DateTime<Utc>,
+    pub reason: MisfireReason,
+}
+
+type ErrorHandler = Arc<dyn Fn(&TaskFailure) + Send + Sync>;
+type MisfireHandler = Arc<dyn Fn(&Misfire) + Send + Sync>;
+
+/// Configuration shared by all tasks of a Zeitschaltuhr.
+struct Settings {
+    error_handler: ErrorHandler,
+    misfire_handler: MisfireHandler,
+    store_error_handler: StoreErrorHandler,
+    default_timeout: Option<std::time::Duration>,
+    wall_clock_check_interval: std::time::Duration,
+    limiter: Option<Arc<ExecutionLimiter>>,
+    exclusion_groups: ExclusionGroups,
+    history_capacity: usize,
+    listeners: Vec<Box<dyn EventListener>>,
+    state: Option<PersistedState>,
+    clock: Arc<dyn Clock>,
+}
+
+/// The writer for checkpoints of tasks together with the checkpoints loaded on startup.
+struct PersistedState {
+    writer: Arc<CheckpointWriter>,
+    checkpoints: HashMap<String, TaskCheckpoint>,
+}
+
+impl Settings {
+    fn emit(&self, event: Event) {
+        for listener in &self.listeners {
+            catch_callback_panic("event listener", || listener.on_event(&event));
+        }
+    }
+
+    fn skip(&self, task_id: TaskId, scheduled_at: DateTime<Utc>, reason: SkipReason) {
+        #[cfg(feature = "tracing")]
+        match reason {
+            SkipReason::Misfire(reason) => {
+                tracing::warn!(%task_id, %scheduled_at, ?reason, "occurrence misfired")
+            }
+            _ => tracing::info!(%task_id, %scheduled_at, ?reason, "occurrence skipped"),
+        }
+        if let SkipReason::Misfire(reason) = reason {
+            let misfire = Misfire {
+                task_id,
+                scheduled_at,
+                reason,
+            };
+            catch_callback_panic("misfire handler", || (self.misfire_handler)(&misfire));
+        }
+        self.emit(Event::OccurrenceSkipped {
+            task_id,
+            scheduled_at,
+            skipped_at: self.clock.now(),
+            reason,
+        });
+    }
+}
+
+/// Run a callback of the user. Callbacks also run in the driver, which must not be stopped by a
+/// panicking callback, so the panic is reported instead.
+fn catch_callback_panic(callback: &str, f: impl FnOnce()) {
+    let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) else {
+        return;
+    };
+    let message = panic_message(&*payload);
+    #[cfg(feature = "tracing")]
+    tracing::error!(callback, message, "callback panicked");
+    #[cfg(not(feature = "tracing"))]
+    eprintln!("{callback} panicked with message {message:?}");
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            error_handler: Arc::new(|failure: &TaskFailure| {
+                eprintln!(
+                    "{} scheduled at {} failed: {}",
+                    failure.task_id, failure.scheduled_at, failure.error
+                );
+            }),
+            misfire_handler: Arc::new(|misfire: &Misfire| {
+                eprintln!(
+                    "{} scheduled at {} was dropped: {:?}",
+                    misfire.task_id, misfire.scheduled_at, misfire.reason
+                );
+            }),
+            store_error_handler: default_store_error_handler(),
+            default_timeout: None,
+            wall_clock_check_interval: std::time::Duration::from_secs(1),
+            limiter: None,
+            exclusion_groups: ExclusionGroups::default(),
+            history_capacity: 10,
+            listeners: Vec::new(),
+            state: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Zeitschaltuhr {
     tasks: Vec<ScheduledTask>,
+    next_task_id: u64,
+    settings: Settings,
 }
 
 impl Zeitschaltuhr {
-    pub fn add_task(&mut self, task: Box<dyn Task>, temporal_iterator: Box<dyn TemporalIterator>) {
-        let scheduled_task = ScheduledTask::new(temporal_iterator, task);
+    /// Set the handler which is called for every failed execution. By default failures are printed to stderr.
+    pub fn set_error_handler(&mut self, handler: impl Fn(&TaskFailure) + Send + Sync + 'static) {
+        self.settings.error_handler = Arc::new(handler);
+    }
+
+    /// Set the handler which is called for every occurrence dropped due to the misfire policy or the
+    /// maximum lateness of a task. By default dropped occurrences are printed to stderr. A panic of
+    /// the handler is printed to stderr or traced as well.
+    pub fn set_misfire_handler(&mut self, handler: impl Fn(&Misfire) + Send + Sync + 'static) {
+        self.settings.misfire_handler = Arc::new(handler);
+    }
+
+    /// Set the handler which is called when checkpoints could not be written to the state store.
+    /// The checkpoints are written again with the next save. By default failures are traced if
+    /// tracing is enabled and printed to stderr otherwise. A panic of the handler is reported alike.
+    pub fn set_store_error_handler(
+        &mut self,
+        handler: impl Fn(&StoreError) + Send + Sync + 'static,
+    ) {
+        // a panic would stop the writer, so that later checkpoints are never written
+        self.settings.store_error_handler = Arc::new(move |error: &StoreError| {
+            catch_callback_panic("store error handler", || handler(error));
+        });
+        if let Some(state) = &self.settings.state {
+            state
+                .writer
+                .set_error_handler(self.settings.store_error_handler.clone());
+        }
+    }
+
+    /// Set the timeout for executions of tasks which do not define their own timeout.
+    pub fn set_default_timeout(&mut self, timeout: std::time::Duration) {
+        self.settings.default_timeout = Some(timeout);
+    }
+
+    /// Set how often a waiting task compares its target time with the wall clock. Shorter intervals
+    /// correct jumps of the system time faster at the cost of more wakeups. Defaults to one second.
+    /// Fails if the interval is zero.
+    pub fn set_wall_clock_check_interval(
+        &mut self,
+        interval: std::time::Duration,
+    ) -> Result<(), ZeitschaltuhrError> {
+        if interval.is_zero() {
+            return Err(ZeitschaltuhrError::ZeroDurationError);
+        }
+        self.settings.wall_clock_check_interval = interval;
+        Ok(())
+    }
+
+    /// Limit the number of executions running at the same time across all tasks. Further executions
+    /// wait until a running execution finishes and are started by priority of their task.
+    pub fn set_max_concurrent_executions(&mut self, max_concurrent_executions: NonZeroUsize) {
+        self.settings.limiter = Some(Arc::new(ExecutionLimiter::new(
+            max_concurrent_executions.get(),
+        )));
+    }
+
+    /// Set how many past executions are kept for every task. Defaults to 10.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.settings.history_capacity = capacity;
+    }
+
+    /// Register a listener which is notified about the events of all tasks. A panicking listener
+    /// does not stop the Zeitschaltuhr, the panic is printed to stderr or traced instead.
+    pub fn add_listener(&mut self, listener: impl EventListener + 'static) {
+        self.settings.listeners.push(Box::new(listener));
+    }
+
+    /// Set the clock which decides when tasks are due. Defaults to the system clock. Timeouts and
+    /// the measured duration of executions always use the real time.
+    pub fn set_clock(&mut self, clock: impl Clock) {
+        self.settings.clock = Arc::new(clock);
+    }
+
+    /// Keep the progress of named tasks in the store. The checkpoints are loaded immediately. Once
+    /// running, named tasks resume their schedule after the time they last fired, so occurrences
+    /// missed while the Zeitschaltuhr was down are handled according to the misfire policy.
+    /// Checkpoints are written in the background and flushed once the Zeitschaltuhr shuts down.
+    pub fn set_state_store(&mut self, store: impl StateStore + 'static) -> Result<(), StoreError> {
+        let checkpoints = store.load()?;
+        self.settings.state = Some(PersistedState {
+            writer: Arc::new(CheckpointWriter::new(
+                Arc::new(store),
+                self.settings.store_error_handler.clone(),
+            )),
+            checkpoints,
+        });
+        Ok(())
+    }
+
+    /// Register a task. The returned id can be used to control the task once the Zeitschaltuhr is running.
+    pub fn add_task(
+        &mut self,
+        task: Box<dyn Task>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+    ) -> TaskId {
+        self.add(task.into(), temporal_iterator, TaskOptions::default())
+    }
+
+    /// Register a task which is executed according to the given options.
+    pub fn add_task_with_options(
+        &mut self,
+        task: Box<dyn Task>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+        options: TaskOptions,
+    ) -> TaskId {
+        self.add(task.into(), temporal_iterator, options)
+    }
+
+    /// Register an asynchronous task. The returned id can be used to control the task once the Zeitschaltuhr is running.
+    pub fn add_async_task(
+        &mut self,
+        task: Box<dyn AsyncTask>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+    ) -> TaskId {
+        self.add(task.into(), temporal_iterator, TaskOptions::default())
+    }
+
+    /// Register an asynchronous task which is executed according to the given options.
+    pub fn add_async_task_with_options(
+        &mut self,
+        task: Box<dyn AsyncTask>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+        options: TaskOptions,
+    ) -> TaskId {
+        self.add(task.into(), temporal_iterator, options)
+    }
+
+    fn add(
+        &mut self,
+        task: TaskKind,
+        temporal_iterator: Box<dyn TemporalIterator>,
+        options: TaskOptions,
+    ) -> TaskId {
+        let id = TaskId(self.next_task_id);
+        self.next_task_id += 1;
+
+        let scheduled_task = ScheduledTask::new(id, temporal_iterator, task, options);
         self.tasks.push(scheduled_task);
+
+        id
     }
 
-    pub fn run(self) {
-        for scheduled_task in self.tasks {
-            tokio::spawn(async move {
-                execute_task(scheduled_task).await;
+    /// The next `count` occurrences of all registered tasks in time order.
+    pub fn upcoming_runs(&self, count: usize) -> Vec<UpcomingRun> {
+        upcoming_runs(self.tasks.iter(), self.settings.clock.now(), None, count)
+    }
+
+    /// The occurrences of all registered tasks after `start` until before `end` in time order.
+    pub fn upcoming_runs_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<UpcomingRun> {
+        upcoming_runs(self.tasks.iter(), start, Some(end), usize::MAX)
+    }
+
+    /// Simulate the executions the registered tasks would perform for occurrences from `start` until
+    /// before `end`, without executing or waiting for anything. Executions are assumed to take the
+    /// expected duration of their task. Failures, retries, the concurrency limit and exclusion groups
+    /// are not simulated.
+    pub fn simulate(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> SimulationReport {
+        let mut report = SimulationReport::default();
+        for scheduled_task in &self.tasks {
+            let fallback_seed = start.timestamp_nanos_opt().unwrap_or_default() as u64;
+            simulate_task(
+                scheduled_task.id,
+                &*scheduled_task.original_iterator,
+                &scheduled_task.options,
+                fallback_seed ^ scheduled_task.id.0,
+                start,
+                end,
+                &mut report,
+            );
+        }
+        report.sort();
+        report
+    }
+
+    /// Start executing all registered tasks. Returns a handle to control the running tasks.
+    pub fn run(self) -> ZeitschaltuhrHandle {
+        let (shutdown, _) = watch::channel(false);
+        let (commands, command_receiver) = mpsc::unbounded_channel();
+        let settings = Arc::new(self.settings);
+        let state = Arc::new(Mutex::new(HandleState {
+            tasks: HashMap::new(),
+            released: HashMap::new(),
+            next_task_id: self.next_task_id,
+            settings: settings.clone(),
+            shutdown,
+            commands,
+            driver: None,
+        }));
+        let mut driver = Driver::new(settings.clone(), Arc::downgrade(&state));
+
+        let (driven_tasks, shutdown): (Vec<DrivenTask>, _) = {
+            let mut handle_state = state.lock().unwrap();
+            let driven_tasks = self
+                .tasks
+                .into_iter()
+                .map(|scheduled_task| {
+                    let id = scheduled_task.id;
+                    let (running_task, driven_task) = RunningTask::new(
+                        scheduled_task,
+                        &settings,
+                        handle_state.shutdown.subscribe(),
+                    );
+                    handle_state.tasks.insert(id, running_task);
+                    driven_task
+                })
+                .collect();
+            (driven_tasks, handle_state.shutdown.subscribe())
+        };
+        // the handle is not locked, because adding an exhausted task releases it from the handle
+        for driven_task in driven_tasks {
+            driver.add(driven_task);
+        }
+        state.lock().unwrap().driver = Some(tokio::spawn(driver.run(command_receiver, shutdown)));
+
+        ZeitschaltuhrHandle { state }
+    }
+}
+
+/// Handle to a running Zeitschaltuhr to add, remove, pause or resume tasks.
+/// The handle can be cloned to control the Zeitschaltuhr from multiple places.
+/// Dropping the handle leaves the tasks running.
+#[derive(Clone)]
+pub struct ZeitschaltuhrHandle {
+    state: Arc<Mutex<HandleState>>,
+}
+
+struct HandleState {
+    tasks: HashMap<TaskId, RunningTask>,
+    /// Histories of tasks which were released because their schedule is exhausted.
+    released: HashMap<TaskId, Arc<ExecutionHistory>>,
+    next_task_id: u64,
+    settings: Arc<Settings>,
+    shutdown: watch::Sender<bool>,
+    commands: mpsc::UnboundedSender<Command>,
+    driver: Option<JoinHandle<()>>,
+}
+
+impl ZeitschaltuhrHandle {
+    /// Register and start a task while the Zeitschaltuhr is running.
+    /// Fails if the Zeitschaltuhr is shut down.
+    pub fn add_task(
+        &self,
+        task: Box<dyn Task>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+    ) -> Result<TaskId, ZeitschaltuhrError> {
+        self.add(task.into(), temporal_iterator, TaskOptions::default())
+    }
+
+    /// Register and start a task which is executed according to the given options.
+    /// Fails if the Zeitschaltuhr is shut down.
+    pub fn add_task_with_options(
+        &self,
+        task: Box<dyn Task>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+        options: TaskOptions,
+    ) -> Result<TaskId, ZeitschaltuhrError> {
+        self.add(task.into(), temporal_iterator, options)
+    }
+
+    /// Register and start an asynchronous task while the Zeitschaltuhr is running.
+    /// Fails if the Zeitschaltuhr is shut down.
+    pub fn add_async_task(
+        &self,
+        task: Box<dyn AsyncTask>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+    ) -> Result<TaskId, ZeitschaltuhrError> {
+        self.add(task.into(), temporal_iterator, TaskOptions::default())
+    }
+
+    /// Register and start an asynchronous task which is executed according to the given options.
+    /// Fails if the Zeitschaltuhr is shut down.
+    pub fn add_async_task_with_options(
+        &self,
+        task: Box<dyn AsyncTask>,
+        temporal_iterator: Box<dyn TemporalIterator>,
+        options: TaskOptions,
+    ) -> Result<TaskId, ZeitschaltuhrError> {
+        self.add(task.into(), temporal_iterator, options)
+    }
+
+    fn add(
+        &self,
+        task: TaskKind,
+        temporal_iterator: Box<dyn TemporalIterator>,
+        options: TaskOptions,
+    ) -> Result<TaskId, ZeitschaltuhrError> {
+        let mut state = self.state.lock().unwrap();
+        if state.is_stopped() {
+            return Err(ZeitschaltuhrError::ShutDownError);
+        }
+
+        let id = TaskId(state.next_task_id);
+        state.next_task_id += 1;
+
+        let scheduled_task = ScheduledTask::new(id, temporal_iterator, task, options);
+        let (running_task, driven_task) =
+            RunningTask::new(scheduled_task, &state.settings, state.shutdown.subscribe());
+        state.send(Command::Add(Box::new(driven_task)))?;
+        state.tasks.insert(id, running_task);
+
+        Ok(id)
+    }
+
+    /// Remove the task. It will not be executed again. Executions in progress are not interrupted.
+    /// Removing a task whose schedule is exhausted drops its history.
+    pub fn remove_task(&self, id: TaskId) -> Result<(), ZeitschaltuhrError> {
+        let mut state = self.state.lock().unwrap();
+        if state.is_stopped() {
+            return Err(ZeitschaltuhrError::ShutDownError);
+        }
+        if state.released.remove(&id).is_some() {
+            return state.send(Command::Remove(id));
+        }
+        let running_task = state
+            .tasks
+            .remove(&id)
+            .ok_or(ZeitschaltuhrError::UnknownTaskError(id))?;
+        running_task.state.send_replace(TaskState::Removed);
+        state.send(Command::Remove(id))
+    }
+
+    /// Cancel the task. It will not be executed again. Same as [`ZeitschaltuhrHandle::remove_task`].
+    pub fn cancel(&self, id: TaskId) -> Result<(), ZeitschaltuhrError> {
+        self.remove_task(id)
+    }
+
+    /// Pause the task. Occurrences which become due while the task is paused are skipped.
+    pub fn pause(&self, id: TaskId) -> Result<(), ZeitschaltuhrError> {
+        self.with_running_task(id, |running_task| {
+            running_task.state.send_replace(TaskState::Paused);
+        })
+    }
+
+    /// Resume a paused task. It will be executed again starting with its next occurrence.
+    pub fn resume(&self, id: TaskId) -> Result<(), ZeitschaltuhrError> {
+        self.with_running_task(id, |running_task| {
+            running_task.state.send_replace(TaskState::Active);
+        })
+    }
+
+    pub fn is_paused(&self, id: TaskId) -> Result<bool, ZeitschaltuhrError> {
+        self.with_running_task(id, |running_task| {
+            *running_task.state.borrow() == TaskState::Paused
+        })
+    }
+
+    /// Latest executions of the task, starting with the oldest. The history of a task whose
+    /// schedule is exhausted stays available until the task is removed.
+    pub fn history(&self, id: TaskId) -> Result<Vec<ExecutionRecord>, ZeitschaltuhrError> {
+        let state = self.state.lock().unwrap();
+        state
+            .tasks
+            .get(&id)
+            .map(|running_task| &running_task.history)
+            .or_else(|| state.released.get(&id))
+            .map(|history| history.records())
+            .ok_or(ZeitschaltuhrError::UnknownTaskError(id))
+    }
+
+    /// The next `count` occurrences of all registered tasks in time order.
+    pub fn upcoming_runs(&self, count: usize) -> Vec<UpcomingRun> {
+        let state = self.state.lock().unwrap();
+        let tasks = state.tasks.values().map(|task| &*task.scheduled_task);
+        upcoming_runs(tasks, state.settings.clock.now(), None, count)
+    }
+
+    /// The occurrences of all registered tasks after `start` until before `end` in time order.
+    pub fn upcoming_runs_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<UpcomingRun> {
+        let state = self.state.lock().unwrap();
+        let tasks = state.tasks.values().map(|task| &*task.scheduled_task);
+        upcoming_runs(tasks, start, Some(end), usize::MAX)
+    }
+
+    /// Ids of all tasks which are currently registered.
+    /// A task whose schedule is exhausted is released once its last execution has finished.
+    pub fn task_ids(&self) -> Vec<TaskId> {
+        let mut ids: Vec<TaskId> = self.state.lock().unwrap().tasks.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Stop triggering new executions and wait until the executions in progress have finished.
+    /// Executions which are still running after the deadline are abandoned and an error is returned.
+    /// An abandoned `AsyncTask` is dropped, an abandoned `Task` keeps running on the blocking thread pool until it returns.
+    pub async fn shutdown(&self, deadline: std::time::Duration) -> Result<(), ZeitschaltuhrError> {
+        let driver = {
+            let mut state = self.state.lock().unwrap();
+            state.shutdown.send_replace(true);
+            state.tasks.clear();
+            state.released.clear();
+            state.driver.take()
+        };
+        let Some(driver) = driver else {
+            return Ok(());
+        };
+        let abort_handle = driver.abort_handle();
+
+        match tokio::time::timeout(deadline, driver).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(error)) if error.is_panic() => Err(ZeitschaltuhrError::DriverPanicError(
+                panic_message(&*error.into_panic()),
+            )),
+            Ok(Err(_)) => Ok(()),
+            Err(_) => {
+                abort_handle.abort();
+                Err(ZeitschaltuhrError::ShutdownTimeoutError)
+            }
+        }
+    }
+
+    fn with_running_task<R>(
+        &self,
+        id: TaskId,
+        f: impl FnOnce(&RunningTask) -> R,
+    ) -> Result<R, ZeitschaltuhrError> {
+        let state = self.state.lock().unwrap();
+        if state.is_stopped() {
+            return Err(ZeitschaltuhrError::ShutDownError);
+        }
+        state
+            .tasks
+            .get(&id)
+            .map(f)
+            .ok_or(ZeitschaltuhrError::UnknownTaskError(id))
+    }
+}
+
+impl HandleState {
+    /// Whether the tasks are not driven anymore, because of a shutdown or a panic of the driver.
+    fn is_stopped(&self) -> bool {
+        *self.shutdown.borrow() || self.commands.is_closed()
+    }
+
+    fn send(&self, command: Command) -> Result<(), ZeitschaltuhrError> {
+        self.commands
+            .send(command)
+            .map_err(|_| ZeitschaltuhrError::ShutDownError)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskState {
+    Active,
+    Paused,
+    Removed,
+}
+
+struct RunningTask {
+    scheduled_task: Arc<ScheduledTask>,
+    state: watch::Sender<TaskState>,
+    history: Arc<ExecutionHistory>,
+}
+
+impl RunningTask {
+    /// Prepare a task to be run. Returns the part kept by the handle and the part kept by the driver.
+    fn new(
+        scheduled_task: ScheduledTask,
+        settings: &Settings,
+        shutdown: watch::Receiver<bool>,
+    ) -> (Self, DrivenTask) {
+        let (state, state_receiver) = watch::channel(TaskState::Active);
+        let signals = Signals {
+            state: state_receiver,
+            shutdown,
+            overdue: Arc::new(watch::Sender::new(0)),
+        };
+        let history = Arc::new(ExecutionHistory::new(settings.history_capacity));
+        let scheduled_task = Arc::new(scheduled_task);
+        let driven_task =
+            DrivenTask::new(scheduled_task.clone(), settings, history.clone(), signals);
+
+        let running_task = Self {
+            scheduled_task,
+            state,
+            history,
+        };
+        (running_task, driven_task)
+    }
+}
+
+/// Signals sent from the handle to a running task, and from its executions to the driver.
+#[derive(Clone)]
+struct Signals {
+    state: watch::Receiver<TaskState>,
+    shutdown: watch::Receiver<bool>,
+    /// Number of executions which exceeded their timeout but have not returned yet.
+    overdue: Arc<watch::Sender<usize>>,
+}
+
+impl Signals {
+    fn is_paused(&self) -> bool {
+        *self.state.borrow() == TaskState::Paused
+    }
+
+    fn is_overdue(&self) -> bool {
+        *self.overdue.borrow() > 0
+    }
+
+    /// Completes once an execution of the task is overdue.
+    async fn overdue(&self) {
+        let _ = self
+            .overdue
+            .subscribe()
+            .wait_for(|overdue| *overdue > 0)
+            .await;
+    }
+
+    /// Mark an execution as overdue until the returned guard is dropped.
+    fn mark_overdue(&self) -> OverdueGuard {
+        self.overdue.send_modify(|overdue| *overdue += 1);
+        OverdueGuard(self.overdue.clone())
+    }
+
+    /// Completes once the task is removed or the Zeitschaltuhr is shut down.
+    async fn stop_requested(&mut self) {
+        tokio::select! {
+            _ = wait_for_signal(&mut self.state, |state| *state == TaskState::Removed) => {}
+            _ = wait_for_signal(&mut self.shutdown, |requested| *requested) => {}
+        }
+    }
+}
+
+struct OverdueGuard(Arc<watch::Sender<usize>>);
+
+impl Drop for OverdueGuard {
+    fn drop(&mut self) {
+        self.0.send_modify(|overdue| *overdue -= 1);
+    }
+}
+
+async fn wait_for_signal<T>(receiver: &mut watch::Receiver<T>, f: impl FnMut(&T) -> bool) {
+    if receiver.wait_for(f).await.is_err() {
+        // the handle was dropped, so the signal can not be sent anymore
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Requests sent from the handle to the driver.
+enum Command {
+    Add(Box<DrivenTask>),
+    Remove(TaskId),
+}
+
+/// Outcome of a future the driver spawned.
+enum Completion {
+    Finished(TaskId),
+    PermitAcquired(TaskId, Option<OwnedSemaphorePermit>),
+    /// The running execution became overdue while occurrences waited for it.
+    Overdue(TaskId),
+}
+
+type Times = Peekable<Box<dyn Iterator<Item = DateTime<Utc>> + Send>>;
+
+/// The state of a task which is needed to schedule its occurrences.
+struct DrivenTask {
+    scheduled_task: Arc<ScheduledTask>,
+    history: Arc<ExecutionHistory>,
+    checkpointer: Option<Arc<Checkpointer>>,
+    signals: Signals,
+    times: Times,
+    overlap_guard: OverlapGuard,
+    /// Occurrences which wait for the previous execution to finish.
+    queued: VecDeque<DateTime<Utc>>,
+    /// Waits for the permit of the first queued occurrence.
+    waiting: Option<AbortHandle>,
+    running: usize,
+    exhausted: bool,
+}
+
+impl DrivenTask {
+    fn new(
+        scheduled_task: Arc<ScheduledTask>,
+        settings: &Settings,
+        history: Arc<ExecutionHistory>,
+        signals: Signals,
+    ) -> Self {
+        let checkpointer = match (&scheduled_task.options.name, &settings.state) {
+            (Some(name), Some(state)) => Some(Arc::new(Checkpointer::new(
+                name.clone(),
+                state.writer.clone(),
+                state.checkpoints.get(name).copied().unwrap_or_default(),
+            ))),
+            _ => None,
+        };
+
+        let mut jitter = scheduled_task.options.jitter.as_ref().map(|jitter| {
+            let fallback_seed = settings
+                .clock
+                .now()
+                .timestamp_nanos_opt()
+                .unwrap_or_default() as u64;
+            JitterSource::new(jitter, fallback_seed ^ scheduled_task.id.0)
+        });
+        // a task with a checkpoint resumes after the time it last fired
+        let times = match checkpointer
+            .as_ref()
+            .and_then(|checkpointer| checkpointer.checkpoint().last_fired)
+        {
+            Some(last_fired) => scheduled_task
+                .original_iterator
+                .iter_times_after(last_fired),
+            None => scheduled_task
+                .original_iterator
+                .iter_times_from(settings.clock.now()),
+        };
+        let times: Box<dyn Iterator<Item = DateTime<Utc>> + Send> =
+            Box::new(times.map(move |time| match jitter.as_mut() {
+                Some(jitter) => jitter.apply(time),
+                None => time,
+            }));
+
+        Self {
+            overlap_guard: OverlapGuard::new(scheduled_task.options.overlap_policy),
+            scheduled_task,
+            history,
+            checkpointer,
+            signals,
+            times: times.peekable(),
+            queued: VecDeque::new(),
+            waiting: None,
+            running: 0,
+            exhausted: false,
+        }
+    }
+
+    fn spawn_execution(
+        &mut self,
+        settings: &Arc<Settings>,
+        executions: &mut JoinSet<Completion>,
+        time: DateTime<Utc>,
+        permit: OwnedSemaphorePermit,
+    ) -> tokio::task::Id {
+        self.running += 1;
+        let task_id = self.scheduled_task.id;
+        let next_occurrence = self.times.peek().copied();
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "task",
+            task = %self.scheduled_task.display_name(),
+            %task_id
+        );
+        let scheduled_task = self.scheduled_task.clone();
+        let settings = settings.clone();
+        let history = self.history.clone();
+        let checkpointer = self.checkpointer.clone();
+        let signals = self.signals.clone();
+        let occurrence = async move {
+            execute_occurrence(
+                &scheduled_task,
+                &settings,
+                &history,
+                checkpointer.as_deref(),
+                time,
+                next_occurrence,
+                signals,
+            )
+            .await;
+            // execute_occurrence only returns once an overdue execution has returned as well
+            drop(permit);
+            Completion::Finished(task_id)
+        };
+        #[cfg(feature = "tracing")]
+        let occurrence = tracing::Instrument::instrument(occurrence, span);
+        executions.spawn(occurrence).id()
+    }
+}
+
+/// Drives all tasks of a Zeitschaltuhr from a single tokio task. The next occurrence of every task
+/// is kept in a min-heap, so only the earliest occurrence across all tasks is waited for.
+struct Driver {
+    settings: Arc<Settings>,
+    tasks: HashMap<TaskId, DrivenTask>,
+    timers: BinaryHeap<Reverse<(DateTime<Utc>, TaskId)>>,
+    executions: JoinSet<Completion>,
+    /// The task of every spawned execution, to account for executions which panicked.
+    execution_tasks: HashMap<tokio::task::Id, TaskId>,
+    /// Exhausted tasks are released from the handles as well.
+    handle: Weak<Mutex<HandleState>>,
+}
+
+impl Driver {
+    fn new(settings: Arc<Settings>, handle: Weak<Mutex<HandleState>>) -> Self {
+        Self {
+            settings,
+            tasks: HashMap::new(),
+            timers: BinaryHeap::new(),
+            executions: JoinSet::new(),
+            execution_tasks: HashMap::new(),
+            handle,
+        }
+    }
+
+    fn add(&mut self, task: DrivenTask) {
+        let id = task.scheduled_task.id;
+        self.settings.emit(Event::TaskAdded {
+            task_id: id,
+            name: task.scheduled_task.options.name.clone(),
+            added_at: self.settings.clock.now(),
+        });
+        self.tasks.insert(id, task);
+        self.schedule_next(id);
+    }
+
+    /// Forget about the task. Entries of removed tasks are left in the timer heap until they
+    /// outnumber the tasks, so the heap is compacted in amortized constant time per removal.
+    fn remove(&mut self, id: TaskId) {
+        if let Some(waiting) = self.tasks.remove(&id).and_then(|task| task.waiting) {
+            waiting.abort();
+        }
+        if self.timers.len() > 2 * self.tasks.len() {
+            let tasks = &self.tasks;
+            self.timers
+                .retain(|Reverse((_, id))| tasks.contains_key(id));
+        }
+        self.settings.emit(Event::TaskRemoved {
+            task_id: id,
+            removed_at: self.settings.clock.now(),
+        });
+    }
+
+    async fn run(
+        mut self,
+        mut commands: mpsc::UnboundedReceiver<Command>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut accepts_commands = true;
+        loop {
+            let settings = self.settings.clone();
+            let next = self.next_timer();
+            tokio::select! {
+                biased;
+                _ = wait_for_signal(&mut shutdown, |requested| *requested) => break,
+                command = commands.recv(), if accepts_commands => match command {
+                    Some(Command::Add(task)) => self.add(*task),
+                    Some(Command::Remove(id)) => self.remove(id),
+                    // once every handle is dropped no further tasks can be added
+                    None => accepts_commands = false,
+                },
+                Some(joined) = self.executions.join_next_with_id(), if !self.executions.is_empty() => {
+                    match joined {
+                        Ok((execution, completion)) => {
+                            self.execution_tasks.remove(&execution);
+                            self.complete(completion);
+                        }
+                        // an execution which panicked outside of its task is finished nonetheless
+                        Err(error) => {
+                            if let Some(id) = self.execution_tasks.remove(&error.id()) {
+                                self.complete(Completion::Finished(id));
+                            }
+                        }
+                    }
+                }
+                _ = sleep_until_next(&*settings.clock, next, settings.wall_clock_check_interval) => {
+                    self.fire_due();
+                }
+            }
+
+            if !accepts_commands && self.tasks.is_empty() && self.executions.is_empty() {
+                break;
+            }
+        }
+
+        // executions in progress are finished before the driver stops
+        for task in self.tasks.values() {
+            if let Some(waiting) = &task.waiting {
+                waiting.abort();
+            }
+        }
+        while self.executions.join_next().await.is_some() {}
+        if let Some(state) = &self.settings.state {
+            state.writer.flush().await;
+        }
+    }
+
+    /// The earliest occurrence of a task which is still registered. Entries of removed tasks on top
+    /// of the heap are dropped, so they do not wake up the driver.
+    fn next_timer(&mut self) -> Option<DateTime<Utc>> {
+        while let Some(Reverse((_, id))) = self.timers.peek() {
+            if self.tasks.contains_key(id) {
+                break;
+            }
+            self.timers.pop();
+        }
+        self.timers.peek().map(|Reverse((time, _))| *time)
+    }
+
+    fn fire_due(&mut self) {
+        let now = self.settings.clock.now();
+        while let Some(Reverse((time, id))) = self.timers.peek().copied() {
+            if time > now {
+                break;
+            }
+            self.timers.pop();
+            self.fire(id, time, now);
+        }
+    }
+
+    fn fire(&mut self, id: TaskId, time: DateTime<Utc>, now: DateTime<Utc>) {
+        let settings = &self.settings;
+        let Some(task) = self.tasks.get_mut(&id) else {
+            return;
+        };
+
+        // after a stall several occurrences can be due at once
+        let mut due = vec![time];
+        while let Some(time) = task.times.next_if(|time| *time <= now) {
+            due.push(time);
+        }
+        for time in &due {
+            settings.emit(Event::TriggerFired {
+                task_id: id,
+                scheduled_at: *time,
+                fired_at: now,
             });
         }
+        if let (Some(checkpointer), Some(last)) = (&task.checkpointer, due.iter().max()) {
+            checkpointer.fired(*last);
+        }
+
+        // occurrences that are due while the task is paused are skipped
+        if task.signals.is_paused() {
+            for time in due {
+                settings.skip(id, time, SkipReason::Paused);
+            }
+            self.schedule_next(id);
+            return;
+        }
+
+        let options = &task.scheduled_task.options;
+        let due = options
+            .misfire_policy
+            .resolve(due, options.max_lateness, now);
+        for (time, reason) in due.dropped {
+            settings.skip(id, time, SkipReason::Misfire(reason));
+        }
+        task.queued.extend(due.fire);
+        self.admit(id);
+    }
+
+    /// Start the queued occurrences of the task as far as its overlap policy allows. A task which
+    /// waits for a running execution is scheduled again once the waiting occurrence has started.
+    /// An overdue execution may never return, so occurrences do not wait for it but are skipped.
+    fn admit(&mut self, id: TaskId) {
+        let Some(task) = self.tasks.get_mut(&id) else {
+            return;
+        };
+
+        while let Some(&time) = task.queued.front() {
+            if let Some(permit) = task.overlap_guard.try_acquire() {
+                task.queued.pop_front();
+                let execution =
+                    task.spawn_execution(&self.settings, &mut self.executions, time, permit);
+                self.execution_tasks.insert(execution, id);
+            } else if task.overlap_guard.queues() && !task.signals.is_overdue() {
+                let overlap_guard = task.overlap_guard.clone();
+                let signals = task.signals.clone();
+                let waiting = self.executions.spawn(async move {
+                    tokio::select! {
+                        biased;
+                        permit = overlap_guard.acquire() => Completion::PermitAcquired(id, permit),
+                        _ = signals.overdue() => Completion::Overdue(id),
+                    }
+                });
+                task.waiting = Some(waiting);
+                return;
+            } else {
+                // occurrences that are due while the previous execution is running are skipped
+                task.queued.pop_front();
+                self.settings.skip(id, time, SkipReason::Overlapping);
+            }
+        }
+        self.schedule_next(id);
+    }
+
+    fn schedule_next(&mut self, id: TaskId) {
+        let Some(task) = self.tasks.get_mut(&id) else {
+            return;
+        };
+
+        match task.times.next() {
+            Some(next) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(task_id = %id, target = %next, "sleeping until next occurrence");
+                self.timers.push(Reverse((next, id)));
+            }
+            None => {
+                task.exhausted = true;
+                self.release_if_exhausted(id);
+            }
+        }
+    }
+
+    fn complete(&mut self, completion: Completion) {
+        match completion {
+            Completion::Finished(id) => {
+                if let Some(task) = self.tasks.get_mut(&id) {
+                    task.running -= 1;
+                    self.release_if_exhausted(id);
+                }
+            }
+            Completion::PermitAcquired(id, permit) => {
+                let Some(task) = self.tasks.get_mut(&id) else {
+                    return;
+                };
+                task.waiting = None;
+                if let (Some(permit), Some(time)) = (permit, task.queued.pop_front()) {
+                    let execution =
+                        task.spawn_execution(&self.settings, &mut self.executions, time, permit);
+                    self.execution_tasks.insert(execution, id);
+                }
+                self.admit(id);
+            }
+            Completion::Overdue(id) => {
+                if let Some(task) = self.tasks.get_mut(&id) {
+                    task.waiting = None;
+                    self.admit(id);
+                }
+            }
+        }
+    }
+
+    /// Drop a task whose schedule ended once its last execution has finished.
+    fn release_if_exhausted(&mut self, id: TaskId) {
+        if !matches!(self.tasks.get(&id), Some(task) if task.exhausted && task.running == 0) {
+            return;
+        }
+        self.tasks.remove(&id);
+        if let Some(handle) = self.handle.upgrade() {
+            let mut handle = handle.lock().unwrap();
+            if let Some(running_task) = handle.tasks.remove(&id) {
+                handle.released.insert(id, running_task.history);
+            }
+        }
+        self.settings.emit(Event::ScheduleExhausted {
+            task_id: id,
+            exhausted_at: self.settings.clock.now(),
+        });
     }
 }
 
-async fn execute_task(scheduled_task: ScheduledTask) {
-    for time in scheduled_task.original_iterator.iter_times() {
-        sleep_until(to_instant(time)).await;
-        scheduled_task.task.execute();
+/// Sleep until the next occurrence is due. Without any occurrence the driver only waits for commands.
+async fn sleep_until_next(
+    clock: &dyn Clock,
+    next: Option<DateTime<Utc>>,
+    check_interval: std::time::Duration,
+) {
+    match next {
+        Some(next) => sleep_until_wall_clock(clock, next, check_interval).await,
+        None => std::future::pending().await,
     }
 }
 
-fn to_instant(date_time: DateTime<Utc>) -> tokio::time::Instant {
-    let offset_from_epoch = u64::try_from(date_time.timestamp()).unwrap_or_default();
-    let target_system_time =
-        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(offset_from_epoch);
+/// Execute a single occurrence and retry it according to the retry policy of the task.
+/// Retries are given up once they would collide with the next occurrence.
+async fn execute_occurrence(
+    scheduled_task: &ScheduledTask,
+    settings: &Settings,
+    history: &ExecutionHistory,
+    checkpointer: Option<&Checkpointer>,
+    time: DateTime<Utc>,
+    next_occurrence: Option<DateTime<Utc>>,
+    mut signals: Signals,
+) {
+    let task_id = scheduled_task.id;
+    let timeout = scheduled_task.options.timeout.or(settings.default_timeout);
+    let mut attempt = 1;
+    loop {
+        // executions waiting for their group or a slot are not started once a stop is requested
+        let group_guard = match &scheduled_task.options.exclusion_group {
+            Some(group) => {
+                let guard = tokio::select! {
+                    biased;
+                    _ = signals.stop_requested() => return,
+                    guard = settings.exclusion_groups.enter(group) => guard,
+                };
+                match guard {
+                    Some(guard) => Some(guard),
+                    None => {
+                        settings.skip(task_id, time, SkipReason::GroupBusy);
+                        return;
+                    }
+                }
+            }
+            None => None,
+        };
+        let permit = match &settings.limiter {
+            Some(limiter) => tokio::select! {
+                biased;
+                _ = signals.stop_requested() => return,
+                permit = limiter.acquire(scheduled_task.options.priority) => Some(permit),
+            },
+            None => None,
+        };
+        let started_at = settings.clock.now();
+        // the occurrence may have become too late while it waited for its turn
+        if let Some(max_lateness) = scheduled_task.options.max_lateness {
+            let lateness = started_at - time;
+            if attempt == 1 && lateness > max_lateness {
+                let reason = SkipReason::Misfire(MisfireReason::TooLate(lateness));
+                settings.skip(task_id, time, reason);
+                return;
+            }
+        }
+        settings.emit(Event::ExecutionStarted {
+            task_id,
+            scheduled_at: time,
+            started_at,
+            attempt,
+        });
+        let start = std::time::Instant::now();
+        let execution = scheduled_task.task.execute(timeout);
+        #[cfg(feature = "tracing")]
+        let execution = tracing::Instrument::instrument(
+            execution,
+            tracing::info_span!(
+                "execution",
+                task = %scheduled_task.display_name(),
+                scheduled_at = %time,
+                attempt
+            ),
+        );
+        let Execution { result, overdue } = execution.await;
+        let duration = start.elapsed();
+        let finished_at = settings.clock.now();
+
+        history.record(ExecutionRecord {
+            scheduled_at: time,
+            started_at,
+            finished_at,
+            duration,
+            attempt,
+            outcome: ExecutionOutcome::from(&result),
+        });
 
-    // offset from now until target time
-    let now = SystemTime::now();
-    let target_instant = match target_system_time.duration_since(now) {
-        Ok(duration) => Instant::now() + duration,
-        Err(_) => Instant::now(),
-    };
+        let Err(error) = result else {
+            if let Some(checkpointer) = checkpointer {
+                checkpointer.completed(finished_at);
+            }
+            settings.emit(Event::ExecutionFinished {
+                task_id,
+                scheduled_at: time,
+                started_at,
+                finished_at,
+                duration,
+                attempt,
+            });
+            return;
+        };
+        settings.emit(Event::ExecutionFailed {
+            task_id,
+            scheduled_at: time,
+            started_at,
+            finished_at,
+            duration,
+            attempt,
+            error: error.to_string(),
+        });
+        let failure = TaskFailure {
+            task_id,
+            scheduled_at: time,
+            attempt,
+            error,
+        };
+        (settings.error_handler)(&failure);
+
+        // an overdue task keeps its group and its slots until it has actually returned
+        if let Some(overdue) = overdue {
+            let _overdue = signals.mark_overdue();
+            let _ = overdue.await;
+        }
+        drop(permit);
+        drop(group_guard);
+
+        let Some(delay) = scheduled_task
+            .options
+            .retry_policy
+            .as_ref()
+            .and_then(|policy| policy.retry_delay(attempt, settings.clock.now(), next_occurrence))
+        else {
+            return;
+        };
+        tokio::select! {
+            biased;
+            _ = signals.stop_requested() => return,
+            _ = settings.clock.sleep(delay.to_std().unwrap_or_default()) => {}
+        }
+        attempt += 1;
+    }
+}
+
+/// Sleep until the clock reaches `target`. Instead of sleeping for the whole time at once, the
+/// wall clock is checked again at least every `check_interval`. That way adjustments of the system
+/// time, a suspended machine and targets beyond the maximum duration of a tokio sleep are handled.
+async fn sleep_until_wall_clock(
+    clock: &dyn Clock,
+    target: DateTime<Utc>,
+    check_interval: std::time::Duration,
+) {
+    // a target in the past can not be converted and is due immediately
+    while let Ok(remaining) = (target - clock.now()).to_std() {
+        if remaining.is_zero() {
+            return;
+        }
+        clock.sleep(remaining.min(check_interval)).await;
+    }
+}
 
-    tokio::time::Instant::from_std(target_instant)
+/// Merge the occurrences of the tasks after `start`, limited to `count` occurrences before `end`.
+fn upcoming_runs<'a>(
+    tasks: impl Iterator<Item = &'a ScheduledTask>,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    count: usize,
+) -> Vec<UpcomingRun> {
+    let sources = tasks
+        .map(|scheduled_task| {
+            // custom temporal iterators may ignore `start` and begin in the past
+            let times: Box<dyn Iterator<Item = DateTime<Utc>> + Send> = Box::new(
+                scheduled_task
+                    .original_iterator
+                    .iter_times_from(start)
+                    .skip_while(move |time| *time <= start),
+            );
+            (
+                scheduled_task.id,
+                scheduled_task.options.name.clone(),
+                times,
+            )
+        })
+        .collect();
+    MergedRuns::new(sources)
+        .take_while(|run| end.is_none_or(|end| run.scheduled_at < end))
+        .take(count)
+        .collect()
 }
 
 struct ScheduledTask {
+    id: TaskId,
     original_iterator: Box<dyn TemporalIterator + Send + Sync>,
-    task: Box<dyn Task>,
+    task: TaskKind,
+    options: TaskOptions,
 }
 
 impl ScheduledTask {
     fn new(
+        id: TaskId,
         original_iterator: Box<dyn TemporalIterator + Send + Sync>,
-        task: Box<dyn Task>,
+        task: TaskKind,
+        options: TaskOptions,
     ) -> Self {
         Self {
+            id,
             original_iterator,
             task,
+            options,
+        }
+    }
+
+    /// The name of the task, which falls back to its id.
+    #[cfg(feature = "tracing")]
+    fn display_name(&self) -> String {
+        match &self.options.name {
+            Some(name) => name.clone(),
+            None => self.id.to_string(),
         }
     }
 }
@@ -69,17 +1359,119 @@ impl ScheduledTask {
 #[cfg(test)]
 mod tests {
 
-    use chrono::Duration;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use chrono::{Duration, TimeZone};
 
-    use crate::{period::Period, task::PrintingTask};
+    use crate::{
+        clock::MockClock,
+        event::Event,
+        exclusion::{ExclusionBehavior, ExclusionGroup},
+        history::ExecutionOutcome,
+        jitter::Jitter,
+        misfire::MisfirePolicy,
+        overlap::OverlapPolicy,
+        period::{Period, Precision},
+        retry::RetryPolicy,
+        store::MemoryStore,
+        task::{PrintingTask, TaskResult, TimeoutError},
+    };
 
     use super::*;
 
-    #[test]
-    fn that_zeitschaltuhr_can_be_created() {
-        let zeitschaltuhr = Zeitschaltuhr::default();
+    struct CountingTask(Arc<AtomicUsize>);
 
-        assert!(zeitschaltuhr.tasks.is_empty());
+    impl Task for CountingTask {
+        fn execute(&self) -> TaskResult {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn every_second() -> Box<Period> {
+        Box::new(Period::starting_at(Utc::now(), Duration::seconds(1)).unwrap())
+    }
+
+    fn mock_clock() -> MockClock {
+        MockClock::new(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap())
+    }
+
+    fn every_second_of(clock: &MockClock) -> Box<Period> {
+        Box::new(Period::starting_at(clock.now(), Duration::seconds(1)).unwrap())
+    }
+
+    /// Give the running tasks time to react to an advanced mock clock. In tests which start with a
+    /// paused tokio clock, this returns once every task is idle instead of after a fixed time.
+    async fn settle() {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    /// Forward the events of the Zeitschaltuhr into a channel, so that tests can wait for them.
+    fn record_events(zeitschaltuhr: &mut Zeitschaltuhr) -> mpsc::UnboundedReceiver<Event> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        zeitschaltuhr.add_listener(move |event: &Event| {
+            let _ = sender.send(event.clone());
+        });
+        receiver
+    }
+
+    /// Wait for the next event matching the predicate. The test fails if it does not occur.
+    async fn wait_for_event(
+        events: &mut mpsc::UnboundedReceiver<Event>,
+        predicate: impl Fn(&Event) -> bool,
+    ) -> Event {
+        let event = async {
+            loop {
+                let event = events.recv().await.expect("no more events are emitted");
+                if predicate(&event) {
+                    return event;
+                }
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), event)
+            .await
+            .expect("the expected event did not occur")
+    }
+
+    /// Keeps blocking test tasks running until the test opens it. A forgotten gate opens by
+    /// itself after a while, so that a failing test does not hang.
+    #[derive(Clone, Default)]
+    struct Gate(Arc<(Mutex<bool>, std::sync::Condvar)>);
+
+    impl Gate {
+        fn open(&self) {
+            let (open, opened) = &*self.0;
+            *open.lock().unwrap() = true;
+            opened.notify_all();
+        }
+
+        fn pass(&self) {
+            let (open, opened) = &*self.0;
+            let _open = opened
+                .wait_timeout_while(
+                    open.lock().unwrap(),
+                    std::time::Duration::from_secs(10),
+                    |open| !*open,
+                )
+                .unwrap();
+        }
+    }
+
+    /// Advance the mock clock second by second and let the tasks execute every due occurrence.
+    async fn advance_seconds(clock: &MockClock, seconds: usize) {
+        for _ in 0..seconds {
+            clock.advance(Duration::seconds(1));
+            settle().await;
+        }
+    }
+
+    #[test]
+    fn that_zeitschaltuhr_can_be_created() {
+        let zeitschaltuhr = Zeitschaltuhr::default();
+
+        assert!(zeitschaltuhr.tasks.is_empty());
     }
 
     #[test]
@@ -92,4 +1484,1526 @@ mod tests {
 
         assert_eq!(1, zeitschaltuhr.tasks.len());
     }
+
+    #[test]
+    fn that_wall_clock_check_interval_must_not_be_zero() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+
+        assert_eq!(
+            Err(ZeitschaltuhrError::ZeroDurationError),
+            zeitschaltuhr.set_wall_clock_check_interval(std::time::Duration::ZERO)
+        );
+        assert_eq!(
+            Ok(()),
+            zeitschaltuhr.set_wall_clock_check_interval(std::time::Duration::from_millis(1))
+        );
+    }
+
+    #[test]
+    fn that_add_task_returns_distinct_ids() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+
+        let first =
+            zeitschaltuhr.add_task(Box::new(PrintingTask::new("a".to_string())), every_second());
+        let second =
+            zeitschaltuhr.add_task(Box::new(PrintingTask::new("b".to_string())), every_second());
+
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn that_handle_returns_error_for_unknown_task() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let id =
+            zeitschaltuhr.add_task(Box::new(PrintingTask::new("a".to_string())), every_second());
+        let handle = zeitschaltuhr.run();
+
+        handle.cancel(id).unwrap();
+
+        assert_eq!(
+            Err(ZeitschaltuhrError::UnknownTaskError(id)),
+            handle.pause(id)
+        );
+        assert_eq!(
+            Err(ZeitschaltuhrError::UnknownTaskError(id)),
+            handle.resume(id)
+        );
+        assert_eq!(
+            Err(ZeitschaltuhrError::UnknownTaskError(id)),
+            handle.cancel(id)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_paused_task_is_not_executed_while_others_keep_running() {
+        let paused_counter = Arc::new(AtomicUsize::new(0));
+        let running_counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let paused_id = zeitschaltuhr.add_task(
+            Box::new(CountingTask(paused_counter.clone())),
+            every_second_of(&clock),
+        );
+        zeitschaltuhr.add_task(
+            Box::new(CountingTask(running_counter.clone())),
+            every_second_of(&clock),
+        );
+        let handle = zeitschaltuhr.run();
+
+        handle.pause(paused_id).unwrap();
+        advance_seconds(&clock, 2).await;
+
+        assert!(handle.is_paused(paused_id).unwrap());
+        assert_eq!(0, paused_counter.load(Ordering::SeqCst));
+        assert_eq!(2, running_counter.load(Ordering::SeqCst));
+
+        handle.resume(paused_id).unwrap();
+        advance_seconds(&clock, 1).await;
+
+        assert_eq!(1, paused_counter.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_cancelled_task_is_not_executed_again() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let id = zeitschaltuhr.add_task(
+            Box::new(CountingTask(counter.clone())),
+            every_second_of(&clock),
+        );
+        let handle = zeitschaltuhr.run();
+
+        handle.cancel(id).unwrap();
+        advance_seconds(&clock, 2).await;
+
+        assert_eq!(0, counter.load(Ordering::SeqCst));
+    }
+
+    /// Blocks until its gate is opened.
+    struct BlockingTask {
+        gate: Gate,
+        started: Arc<AtomicUsize>,
+        finished: Arc<AtomicUsize>,
+    }
+
+    impl Task for BlockingTask {
+        fn execute(&self) -> TaskResult {
+            self.started.fetch_add(1, Ordering::SeqCst);
+            self.gate.pass();
+            self.finished.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn blocking_task() -> (BlockingTask, Gate, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let gate = Gate::default();
+        let started = Arc::new(AtomicUsize::new(0));
+        let finished = Arc::new(AtomicUsize::new(0));
+        let task = BlockingTask {
+            gate: gate.clone(),
+            started: started.clone(),
+            finished: finished.clone(),
+        };
+        (task, gate, started, finished)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_shutdown_waits_for_executions_in_progress() {
+        let (task, gate, started, finished) = blocking_task();
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_task(Box::new(task), every_second_of(&clock));
+        let handle = zeitschaltuhr.run();
+
+        clock.advance(Duration::seconds(1));
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::ExecutionStarted { .. })
+        })
+        .await;
+        let (result, _) = tokio::join!(handle.shutdown(std::time::Duration::from_secs(5)), async {
+            gate.open()
+        });
+
+        assert_eq!(Ok(()), result);
+        assert_eq!(1, finished.load(Ordering::SeqCst));
+
+        advance_seconds(&clock, 2).await;
+        assert_eq!(1, started.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_shutdown_returns_error_when_deadline_is_exceeded() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_async_task(
+            Box::new(HangingTask(started.clone())),
+            every_second_of(&clock),
+        );
+        let handle = zeitschaltuhr.run();
+
+        clock.advance(Duration::seconds(1));
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::ExecutionStarted { .. })
+        })
+        .await;
+        let result = handle.shutdown(std::time::Duration::from_millis(100)).await;
+
+        assert_eq!(Err(ZeitschaltuhrError::ShutdownTimeoutError), result);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_task_can_be_added_and_removed_while_running() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let handle = zeitschaltuhr.run();
+        let control = handle.clone();
+
+        let id = control
+            .add_task(
+                Box::new(CountingTask(counter.clone())),
+                every_second_of(&clock),
+            )
+            .unwrap();
+        assert_eq!(vec![id], handle.task_ids());
+
+        advance_seconds(&clock, 2).await;
+        assert_eq!(2, counter.load(Ordering::SeqCst));
+
+        handle.remove_task(id).unwrap();
+        advance_seconds(&clock, 1).await;
+
+        assert!(handle.task_ids().is_empty());
+        assert_eq!(2, counter.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn that_ids_of_added_tasks_continue_after_registered_tasks() {
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let registered =
+            zeitschaltuhr.add_task(Box::new(PrintingTask::new("a".to_string())), every_second());
+        let handle = zeitschaltuhr.run();
+
+        let added = handle
+            .add_task(Box::new(PrintingTask::new("b".to_string())), every_second())
+            .unwrap();
+
+        assert!(added > registered);
+    }
+
+    #[tokio::test]
+    async fn that_task_can_not_be_added_after_shutdown() {
+        let handle = Zeitschaltuhr::default().run();
+
+        handle
+            .shutdown(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+        let result = handle.add_task(Box::new(PrintingTask::new("a".to_string())), every_second());
+
+        assert_eq!(Err(ZeitschaltuhrError::ShutDownError), result);
+    }
+
+    struct AsyncCountingTask(Arc<AtomicUsize>);
+
+    impl AsyncTask for AsyncCountingTask {
+        fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>> {
+            Box::pin(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_async_task_is_executed() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.add_async_task(
+            Box::new(AsyncCountingTask(counter.clone())),
+            every_second_of(&clock),
+        );
+        let handle = zeitschaltuhr.run();
+
+        advance_seconds(&clock, 2).await;
+
+        assert_eq!(2, counter.load(Ordering::SeqCst));
+        assert_eq!(
+            Ok(()),
+            handle.shutdown(std::time::Duration::from_secs(1)).await
+        );
+    }
+
+    struct FailingTask;
+
+    impl Task for FailingTask {
+        fn execute(&self) -> TaskResult {
+            Err("something went wrong".into())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_failed_execution_is_passed_to_error_handler() {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let recorded = failures.clone();
+        zeitschaltuhr.set_error_handler(move |failure| {
+            recorded.lock().unwrap().push((
+                failure.task_id,
+                failure.scheduled_at,
+                failure.error.to_string(),
+            ));
+        });
+        let id = zeitschaltuhr.add_task(Box::new(FailingTask), every_second_of(&clock));
+        let handle = zeitschaltuhr.run();
+
+        advance_seconds(&clock, 1).await;
+        handle
+            .shutdown(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        let failures = failures.lock().unwrap();
+        assert_eq!(1, failures.len());
+        let (task_id, scheduled_at, error) = &failures[0];
+        assert_eq!(id, *task_id);
+        assert_eq!(clock.now(), *scheduled_at);
+        assert_eq!("something went wrong", error);
+    }
+
+    struct PanickingTask;
+
+    impl Task for PanickingTask {
+        fn execute(&self) -> TaskResult {
+            panic!("task panicked");
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_panicking_task_is_reported_as_failure_and_keeps_being_scheduled() {
+        let failures = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let recorded = failures.clone();
+        zeitschaltuhr.set_error_handler(move |_| {
+            recorded.fetch_add(1, Ordering::SeqCst);
+        });
+        zeitschaltuhr.add_task(Box::new(PanickingTask), every_second_of(&clock));
+        let _handle = zeitschaltuhr.run();
+
+        advance_seconds(&clock, 3).await;
+
+        assert_eq!(3, failures.load(Ordering::SeqCst));
+    }
+
+    struct AsyncPanickingTask;
+
+    impl AsyncTask for AsyncPanickingTask {
+        fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>> {
+            Box::pin(async move { panic!("task panicked") })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_panicking_async_task_is_reported_as_failure_and_released() {
+        let failures = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let recorded = failures.clone();
+        zeitschaltuhr.set_error_handler(move |_| {
+            recorded.fetch_add(1, Ordering::SeqCst);
+        });
+        zeitschaltuhr.add_async_task(
+            Box::new(AsyncPanickingTask),
+            Box::new(At(vec![clock.now()])),
+        );
+        let handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        assert_eq!(1, failures.load(Ordering::SeqCst));
+        assert!(handle.task_ids().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_task_is_released_when_its_execution_panics_outside_of_the_task() {
+        let exhausted = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.set_error_handler(|_| panic!("error handler panicked"));
+        let recorded = exhausted.clone();
+        zeitschaltuhr.add_listener(move |event: &Event| {
+            if let Event::ScheduleExhausted { .. } = event {
+                recorded.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        zeitschaltuhr.add_task(Box::new(FailingTask), Box::new(At(vec![clock.now()])));
+        let handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        assert_eq!(1, exhausted.load(Ordering::SeqCst));
+        assert!(handle.task_ids().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_panicking_listener_does_not_stop_the_zeitschaltuhr() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.add_listener(|_: &Event| panic!("listener panicked"));
+        zeitschaltuhr.add_task(
+            Box::new(CountingTask(counter.clone())),
+            every_second_of(&clock),
+        );
+        let handle = zeitschaltuhr.run();
+
+        advance_seconds(&clock, 2).await;
+        let added = handle.add_task(
+            Box::new(CountingTask(counter.clone())),
+            every_second_of(&clock),
+        );
+        advance_seconds(&clock, 1).await;
+
+        assert!(added.is_ok());
+        assert_eq!(4, counter.load(Ordering::SeqCst));
+        assert_eq!(
+            Ok(()),
+            handle.shutdown(std::time::Duration::from_secs(1)).await
+        );
+    }
+
+    /// Panics once it is told to, which stops the driver.
+    #[derive(Clone)]
+    struct PanickingClock {
+        clock: MockClock,
+        panics: Arc<AtomicBool>,
+    }
+
+    impl Clock for PanickingClock {
+        fn now(&self) -> DateTime<Utc> {
+            if self.panics.load(Ordering::SeqCst) {
+                panic!("clock panicked");
+            }
+            self.clock.now()
+        }
+
+        fn sleep(
+            &self,
+            duration: std::time::Duration,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            self.clock.sleep(duration)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_panic_of_the_driver_is_reported() {
+        let clock = mock_clock();
+        let panics = Arc::new(AtomicBool::new(false));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(PanickingClock {
+            clock: clock.clone(),
+            panics: panics.clone(),
+        });
+        let id = zeitschaltuhr.add_task(
+            Box::new(PrintingTask::new("a".to_string())),
+            every_second_of(&clock),
+        );
+        let handle = zeitschaltuhr.run();
+        settle().await;
+
+        panics.store(true, Ordering::SeqCst);
+        clock.advance(Duration::seconds(1));
+        settle().await;
+
+        let added = handle.add_task(
+            Box::new(PrintingTask::new("b".to_string())),
+            every_second_of(&clock),
+        );
+        assert_eq!(Err(ZeitschaltuhrError::ShutDownError), added);
+        assert_eq!(Err(ZeitschaltuhrError::ShutDownError), handle.pause(id));
+        assert_eq!(
+            Err(ZeitschaltuhrError::ShutDownError),
+            handle.remove_task(id)
+        );
+        assert_eq!(
+            Err(ZeitschaltuhrError::DriverPanicError(
+                "clock panicked".to_string()
+            )),
+            handle.shutdown(std::time::Duration::from_secs(1)).await
+        );
+    }
+
+    struct FlakyTask {
+        attempts: Arc<AtomicUsize>,
+        failures_per_occurrence: usize,
+    }
+
+    impl Task for FlakyTask {
+        fn execute(&self) -> TaskResult {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt <= self.failures_per_occurrence {
+                Err(format!("attempt {attempt} failed").into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_failed_execution_is_retried_according_to_retry_policy() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let failed_attempts = Arc::new(Mutex::new(Vec::new()));
+        let task = FlakyTask {
+            attempts: attempts.clone(),
+            failures_per_occurrence: 2,
+        };
+        let options = TaskOptions::default()
+            .with_retry_policy(RetryPolicy::fixed(Duration::milliseconds(50), 5));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let recorded = failed_attempts.clone();
+        zeitschaltuhr.set_error_handler(move |failure| {
+            recorded.lock().unwrap().push(failure.attempt);
+        });
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_task_with_options(
+            Box::new(task),
+            Box::new(At(vec![Utc::now()])),
+            options,
+        );
+        let handle = zeitschaltuhr.run();
+
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::ExecutionFinished { .. })
+        })
+        .await;
+        handle
+            .shutdown(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+        assert_eq!(vec![1, 2], *failed_attempts.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn that_overdue_task_does_not_stall_its_schedule() {
+        // timeouts of sync tasks need the real time, as the overdue task blocks a thread
+        let (task, gate, started, _) = blocking_task();
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.set_error_handler(|_| {});
+        zeitschaltuhr.set_default_timeout(std::time::Duration::from_millis(10));
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_task(Box::new(task), every_second_of(&clock));
+        let _handle = zeitschaltuhr.run();
+
+        clock.advance(Duration::seconds(1));
+        let overdue = TimeoutError::Overdue(std::time::Duration::from_millis(10)).to_string();
+        wait_for_event(
+            &mut events,
+            |event| matches!(event, Event::ExecutionFailed { error, .. } if *error == overdue),
+        )
+        .await;
+
+        // occurrences which are due while the task is overdue are reported instead of waiting
+        for _ in 0..2 {
+            clock.advance(Duration::seconds(1));
+            let skipped = wait_for_event(&mut events, |event| {
+                matches!(event, Event::OccurrenceSkipped { .. })
+            })
+            .await;
+            assert!(matches!(
+                skipped,
+                Event::OccurrenceSkipped {
+                    scheduled_at,
+                    reason: SkipReason::Overlapping,
+                    ..
+                } if scheduled_at == clock.now()
+            ));
+        }
+        assert_eq!(1, started.load(Ordering::SeqCst));
+
+        // once the overdue execution has returned, the occurrences are executed again
+        gate.open();
+        loop {
+            clock.advance(Duration::seconds(1));
+            let event = wait_for_event(&mut events, |event| {
+                matches!(
+                    event,
+                    Event::ExecutionStarted { .. } | Event::OccurrenceSkipped { .. }
+                )
+            })
+            .await;
+            if matches!(event, Event::ExecutionStarted { .. }) {
+                break;
+            }
+        }
+    }
+
+    struct HangingTask(Arc<AtomicUsize>);
+
+    impl AsyncTask for HangingTask {
+        fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>> {
+            Box::pin(async move {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                Ok(())
+            })
+        }
+    }
+
+    async fn started_executions_with_overlap_policy(overlap_policy: OverlapPolicy) -> usize {
+        let started = Arc::new(AtomicUsize::new(0));
+        let options = TaskOptions::default().with_overlap_policy(overlap_policy);
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.add_async_task_with_options(
+            Box::new(HangingTask(started.clone())),
+            every_second_of(&clock),
+            options,
+        );
+        let _handle = zeitschaltuhr.run();
+
+        advance_seconds(&clock, 3).await;
+
+        started.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_occurrences_are_skipped_while_execution_is_running() {
+        let started = started_executions_with_overlap_policy(OverlapPolicy::Skip).await;
+
+        assert_eq!(1, started);
+    }
+
+    /// Set up a driver for the given tasks without running it, so that tests can step through it
+    /// and inspect its state.
+    fn driver_with_tasks(
+        clock: &MockClock,
+        tasks: impl IntoIterator<Item = TaskKind>,
+        schedule: impl Fn() -> Box<dyn TemporalIterator>,
+    ) -> (Driver, watch::Sender<bool>) {
+        let settings = Arc::new(Settings {
+            clock: Arc::new(clock.clone()),
+            ..Settings::default()
+        });
+        let (shutdown, shutdown_receiver) = watch::channel(false);
+        let mut driver = Driver::new(settings.clone(), Weak::new());
+        for (id, task) in (0..).zip(tasks) {
+            let scheduled_task =
+                ScheduledTask::new(TaskId(id), schedule(), task, TaskOptions::default());
+            let (_, driven_task) =
+                RunningTask::new(scheduled_task, &settings, shutdown_receiver.clone());
+            driver.add(driven_task);
+        }
+        (driver, shutdown)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_queued_occurrence_of_removed_task_is_not_executed() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let task = TaskKind::Async(Arc::new(HangingTask(started.clone())));
+        let (mut driver, _shutdown) = driver_with_tasks(&clock, [task], || every_second_of(&clock));
+        let id = TaskId(0);
+
+        for _ in 0..2 {
+            clock.advance(Duration::seconds(1));
+            driver.fire_due();
+            settle().await;
+        }
+        assert!(driver.tasks[&id].waiting.is_some());
+        assert_eq!(2, driver.executions.len());
+
+        driver.remove(id);
+        let waiting = driver.executions.join_next().await.unwrap();
+        assert!(waiting.is_err_and(|error| error.is_cancelled()));
+        assert!(driver.executions.join_next().await.unwrap().is_ok());
+
+        assert_eq!(1, started.load(Ordering::SeqCst));
+        assert!(driver.timers.is_empty());
+    }
+
+    /// Runs until it is released.
+    struct ReleasedTask(Arc<tokio::sync::Notify>);
+
+    impl AsyncTask for ReleasedTask {
+        fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>> {
+            Box::pin(async move {
+                self.0.notified().await;
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_occurrences_are_queued_while_execution_is_running() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_async_task_with_options(
+            Box::new(ReleasedTask(release.clone())),
+            every_second_of(&clock),
+            TaskOptions::default().with_overlap_policy(OverlapPolicy::Queue),
+        );
+        let _handle = zeitschaltuhr.run();
+        let is_execution = |event: &Event| {
+            matches!(
+                event,
+                Event::ExecutionStarted { .. } | Event::ExecutionFinished { .. }
+            )
+        };
+
+        advance_seconds(&clock, 3).await;
+        let Event::ExecutionStarted { scheduled_at, .. } =
+            wait_for_event(&mut events, is_execution).await
+        else {
+            panic!("the first occurrence did not start");
+        };
+        let first = scheduled_at;
+        settle().await;
+        while let Ok(event) = events.try_recv() {
+            assert!(!is_execution(&event));
+        }
+
+        release.notify_one();
+
+        assert!(matches!(
+            wait_for_event(&mut events, is_execution).await,
+            Event::ExecutionFinished { scheduled_at, .. } if scheduled_at == first
+        ));
+        assert!(matches!(
+            wait_for_event(&mut events, is_execution).await,
+            Event::ExecutionStarted { scheduled_at, .. }
+                if scheduled_at == first + Duration::seconds(1)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_concurrent_executions_are_limited() {
+        let started = started_executions_with_overlap_policy(OverlapPolicy::Concurrent(
+            NonZeroUsize::new(2).unwrap(),
+        ))
+        .await;
+
+        assert_eq!(2, started);
+    }
+
+    /// Fires at the given timestamps.
+    struct At(Vec<DateTime<Utc>>);
+
+    impl TemporalIterator for At {
+        fn iter_times(&self) -> Box<dyn Iterator<Item = DateTime<Utc>> + Send> {
+            Box::new(self.0.clone().into_iter())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_named_task_resumes_after_its_checkpoint() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let now = clock.now();
+        let last_fired = now - Duration::seconds(2);
+        let store = Arc::new(MemoryStore::default());
+        let checkpoint = TaskCheckpoint {
+            last_fired: Some(last_fired),
+            last_completed: None,
+        };
+        store.save("backup", &checkpoint).unwrap();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock);
+        zeitschaltuhr.set_state_store(store.clone()).unwrap();
+        zeitschaltuhr.add_task_with_options(
+            Box::new(CountingTask(counter.clone())),
+            Box::new(At(vec![now - Duration::seconds(3), last_fired, now])),
+            TaskOptions::default().with_name("backup"),
+        );
+        let handle = zeitschaltuhr.run();
+
+        settle().await;
+        handle
+            .shutdown(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        let checkpoint = store.load().unwrap()["backup"];
+        assert_eq!(checkpoint.last_fired, Some(now));
+        assert!(checkpoint.last_completed.is_some());
+    }
+
+    struct FailingStore;
+
+    impl StateStore for FailingStore {
+        fn load(&self) -> Result<HashMap<String, TaskCheckpoint>, StoreError> {
+            Ok(HashMap::new())
+        }
+
+        fn save(&self, _: &str, _: &TaskCheckpoint) -> Result<(), StoreError> {
+            Err("store is broken".into())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_failed_checkpoint_writes_are_passed_to_store_error_handler() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let clock = mock_clock();
+        let now = clock.now();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock);
+        zeitschaltuhr.set_state_store(FailingStore).unwrap();
+        let recorded = errors.clone();
+        zeitschaltuhr.set_store_error_handler(move |error| {
+            recorded.lock().unwrap().push(error.to_string());
+        });
+        zeitschaltuhr.add_task_with_options(
+            Box::new(PrintingTask::new(String::new())),
+            Box::new(At(vec![now])),
+            TaskOptions::default().with_name("backup"),
+        );
+        let handle = zeitschaltuhr.run();
+
+        settle().await;
+        handle
+            .shutdown(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        let errors = errors.lock().unwrap();
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|error| error == "store is broken"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_missed_occurrences_are_handled_according_to_misfire_policy() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let misfires = Arc::new(Mutex::new(Vec::new()));
+        let clock = mock_clock();
+        let now = clock.now();
+        let missed = vec![
+            now - Duration::minutes(3),
+            now - Duration::minutes(2),
+            now - Duration::minutes(1),
+        ];
+        let options = TaskOptions::default()
+            .with_misfire_policy(MisfirePolicy::FireOnce)
+            .with_max_lateness(Duration::seconds(150));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock);
+        let recorded = misfires.clone();
+        zeitschaltuhr.set_misfire_handler(move |misfire| {
+            recorded.lock().unwrap().push(misfire.clone());
+        });
+        let id = zeitschaltuhr.add_task_with_options(
+            Box::new(CountingTask(counter.clone())),
+            Box::new(At(missed.clone())),
+            options,
+        );
+        let handle = zeitschaltuhr.run();
+
+        settle().await;
+        handle
+            .shutdown(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert_eq!(1, counter.load(Ordering::SeqCst));
+        let misfires = misfires.lock().unwrap();
+        assert_eq!(2, misfires.len());
+        assert_eq!(id, misfires[0].task_id);
+        assert_eq!(missed[0], misfires[0].scheduled_at);
+        assert!(matches!(misfires[0].reason, MisfireReason::TooLate(_)));
+        assert_eq!(missed[1], misfires[1].scheduled_at);
+        assert_eq!(MisfireReason::Missed, misfires[1].reason);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_queued_occurrence_exceeding_max_lateness_is_dropped() {
+        let (task, gate, started, _) = blocking_task();
+        let misfires = Arc::new(Mutex::new(Vec::new()));
+        let clock = mock_clock();
+        let options = TaskOptions::default().with_max_lateness(Duration::minutes(1));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let recorded = misfires.clone();
+        zeitschaltuhr.set_misfire_handler(move |misfire| {
+            recorded.lock().unwrap().push(misfire.reason);
+        });
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_task_with_options(
+            Box::new(task),
+            Box::new(At(vec![clock.now(), clock.now()])),
+            options,
+        );
+        let _handle = zeitschaltuhr.run();
+
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::ExecutionStarted { .. })
+        })
+        .await;
+        clock.advance(Duration::minutes(2));
+        gate.open();
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::OccurrenceSkipped { .. })
+        })
+        .await;
+
+        assert_eq!(1, started.load(Ordering::SeqCst));
+        assert_eq!(
+            vec![MisfireReason::TooLate(Duration::minutes(2))],
+            *misfires.lock().unwrap()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_sleep_until_wall_clock_wakes_up_at_target() {
+        let clock = mock_clock();
+        let target = clock.now() + Duration::milliseconds(300);
+        let sleeper = {
+            let clock = clock.clone();
+            tokio::spawn(async move {
+                sleep_until_wall_clock(&clock, target, std::time::Duration::from_millis(50)).await;
+            })
+        };
+
+        clock.advance(Duration::milliseconds(299));
+        settle().await;
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::milliseconds(1));
+        settle().await;
+        assert!(sleeper.is_finished());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_sleep_until_wall_clock_returns_immediately_for_target_in_the_past() {
+        let target = Utc::now() - Duration::days(1);
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            sleep_until_wall_clock(&SystemClock, target, std::time::Duration::from_secs(1)),
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_sleep_until_wall_clock_supports_targets_in_the_far_future() {
+        let target = Utc::now() + Duration::days(365 * 50);
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            sleep_until_wall_clock(&SystemClock, target, std::time::Duration::from_secs(60)),
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+
+    struct AsyncFailingTask(Arc<AtomicUsize>);
+
+    impl AsyncTask for AsyncFailingTask {
+        fn execute(&self) -> Pin<Box<dyn Future<Output = TaskResult> + Send + '_>> {
+            Box::pin(async move {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Err("something went wrong".into())
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_sleep_until_wall_clock_recomputes_deadline_when_clock_is_set_back() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        let target = start + Duration::minutes(10);
+        let sleeper = {
+            let clock = clock.clone();
+            tokio::spawn(async move {
+                sleep_until_wall_clock(&clock, target, std::time::Duration::from_secs(1)).await;
+            })
+        };
+
+        clock.advance(Duration::minutes(9));
+        clock.set(start);
+        clock.advance(Duration::minutes(9));
+        settle().await;
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::minutes(1));
+        settle().await;
+        assert!(sleeper.is_finished());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_tasks_are_executed_when_mock_clock_is_advanced() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.add_async_task(
+            Box::new(AsyncCountingTask(counter.clone())),
+            Box::new(Period::starting_at(start, Duration::hours(1)).unwrap()),
+        );
+        let _handle = zeitschaltuhr.run();
+
+        settle().await;
+        assert_eq!(0, counter.load(Ordering::SeqCst));
+
+        for _ in 0..3 {
+            clock.advance(Duration::hours(1));
+            settle().await;
+        }
+        assert_eq!(3, counter.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_all_tasks_are_driven_by_a_single_tokio_task() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let tasks =
+            (0..10_000).map(|_| TaskKind::Async(Arc::new(AsyncCountingTask(counter.clone()))));
+        let (mut driver, _shutdown) = driver_with_tasks(&clock, tasks, || {
+            Box::new(Period::starting_at(clock.now(), Duration::hours(1)).unwrap())
+        });
+
+        // waiting tasks are timers of the driver, not tokio tasks of their own
+        driver.fire_due();
+        assert_eq!(10_000, driver.timers.len());
+        assert!(driver.executions.is_empty());
+
+        clock.advance(Duration::hours(1));
+        driver.fire_due();
+        assert_eq!(10_000, driver.executions.len());
+        while let Some(completion) = driver.executions.join_next().await {
+            driver.complete(completion.unwrap());
+        }
+
+        assert_eq!(10_000, counter.load(Ordering::SeqCst));
+        assert_eq!(10_000, driver.timers.len());
+    }
+
+    #[test]
+    fn that_removed_tasks_do_not_accumulate_in_the_timer_heap() {
+        let settings = Arc::new(Settings::default());
+        let (_shutdown, shutdown_receiver) = watch::channel(false);
+        let mut driver = Driver::new(settings.clone(), Weak::new());
+        let add_task = |driver: &mut Driver, id: u64| {
+            let scheduled_task = ScheduledTask::new(
+                TaskId(id),
+                Box::new(Period::starting_at(Utc::now(), Duration::days(1)).unwrap()),
+                TaskKind::Sync(Arc::new(PrintingTask::new(String::new()))),
+                TaskOptions::default(),
+            );
+            let (_, driven_task) =
+                RunningTask::new(scheduled_task, &settings, shutdown_receiver.clone());
+            driver.add(driven_task);
+        };
+        add_task(&mut driver, 0);
+
+        for id in 1..10_000 {
+            add_task(&mut driver, id);
+            driver.remove(TaskId(id));
+        }
+
+        assert_eq!(1, driver.tasks.len());
+        assert!(driver.timers.len() <= 2);
+    }
+
+    /// Counts how often the driver goes to sleep, which is once per wakeup.
+    #[derive(Clone)]
+    struct CountingClock {
+        clock: MockClock,
+        sleeps: Arc<AtomicUsize>,
+    }
+
+    impl Clock for CountingClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.clock.now()
+        }
+
+        fn sleep(
+            &self,
+            duration: std::time::Duration,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            self.sleeps.fetch_add(1, Ordering::SeqCst);
+            self.clock.sleep(duration)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_wakeups_do_not_grow_with_the_number_of_tasks() {
+        let clock = mock_clock();
+        let sleeps = Arc::new(AtomicUsize::new(0));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(CountingClock {
+            clock: clock.clone(),
+            sleeps: sleeps.clone(),
+        });
+        for _ in 0..10_000 {
+            zeitschaltuhr.add_task(
+                Box::new(PrintingTask::new(String::new())),
+                Box::new(Period::starting_at(clock.now(), Duration::hours(1)).unwrap()),
+            );
+        }
+        let _handle = zeitschaltuhr.run();
+        settle().await;
+
+        advance_seconds(&clock, 10).await;
+
+        // one wakeup per second of the wall clock check, instead of one per task and second
+        assert!(sleeps.load(Ordering::SeqCst) <= 20);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_task_is_released_once_its_bounded_period_is_exhausted() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let id = zeitschaltuhr.add_task(
+            Box::new(CountingTask(counter.clone())),
+            Box::new(every_second_of(&clock).with_count(4)),
+        );
+        let handle = zeitschaltuhr.run();
+
+        advance_seconds(&clock, 5).await;
+
+        // the first of the four occurrences is at the start, which is not upcoming anymore
+        assert_eq!(3, counter.load(Ordering::SeqCst));
+        assert!(handle.task_ids().is_empty());
+        assert!(matches!(
+            handle.pause(id),
+            Err(ZeitschaltuhrError::UnknownTaskError(_))
+        ));
+        assert_eq!(3, handle.history(id).unwrap().len());
+
+        handle.remove_task(id).unwrap();
+
+        assert!(matches!(
+            handle.history(id),
+            Err(ZeitschaltuhrError::UnknownTaskError(_))
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_millisecond_periods_are_executed_at_their_exact_times() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let id = zeitschaltuhr.add_task(
+            Box::new(PrintingTask::new("sensor".to_string())),
+            Box::new(
+                Period::starting_at_with_precision(
+                    start + Duration::milliseconds(250),
+                    Duration::milliseconds(250),
+                    Precision::Milliseconds,
+                )
+                .unwrap(),
+            ),
+        );
+        let handle = zeitschaltuhr.run();
+
+        for _ in 0..4 {
+            clock.advance(Duration::milliseconds(250));
+            settle().await;
+        }
+
+        let scheduled: Vec<_> = handle
+            .history(id)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.scheduled_at)
+            .collect();
+        assert_eq!(
+            vec![
+                start + Duration::milliseconds(250),
+                start + Duration::milliseconds(500),
+                start + Duration::milliseconds(750),
+                start + Duration::milliseconds(1_000),
+            ],
+            scheduled
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_retries_wait_on_the_clock_of_the_zeitschaltuhr() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        let options =
+            TaskOptions::default().with_retry_policy(RetryPolicy::fixed(Duration::minutes(5), 3));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.set_error_handler(|_| {});
+        zeitschaltuhr.add_async_task_with_options(
+            Box::new(AsyncFailingTask(attempts.clone())),
+            Box::new(At(vec![start])),
+            options,
+        );
+        let _handle = zeitschaltuhr.run();
+
+        settle().await;
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+
+        clock.advance(Duration::minutes(4));
+        settle().await;
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+
+        clock.advance(Duration::minutes(1));
+        settle().await;
+        assert_eq!(2, attempts.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_concurrent_executions_are_limited_across_tasks() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_max_concurrent_executions(NonZeroUsize::new(2).unwrap());
+        for _ in 0..5 {
+            zeitschaltuhr.add_async_task(
+                Box::new(HangingTask(started.clone())),
+                Box::new(At(vec![Utc::now()])),
+            );
+        }
+        let _handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        assert_eq!(2, started.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_executions_waiting_for_a_slot_are_not_started_after_shutdown() {
+        let (task, gate, started, finished) = blocking_task();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_max_concurrent_executions(NonZeroUsize::MIN);
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_task(Box::new(task), Box::new(At(vec![Utc::now()])));
+        for _ in 0..2 {
+            let task = BlockingTask {
+                gate: gate.clone(),
+                started: started.clone(),
+                finished: finished.clone(),
+            };
+            zeitschaltuhr.add_task(Box::new(task), Box::new(At(vec![Utc::now()])));
+        }
+        let handle = zeitschaltuhr.run();
+
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::ExecutionStarted { .. })
+        })
+        .await;
+        let (result, _) = tokio::join!(handle.shutdown(std::time::Duration::from_secs(5)), async {
+            gate.open()
+        });
+
+        assert_eq!(Ok(()), result);
+        assert_eq!(1, started.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_executions_waiting_for_their_exclusion_group_are_not_started_after_shutdown() {
+        let (task, gate, started, finished) = blocking_task();
+        let options = TaskOptions::default()
+            .with_exclusion_group(ExclusionGroup::new("database", ExclusionBehavior::Wait));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        let mut events = record_events(&mut zeitschaltuhr);
+        zeitschaltuhr.add_task_with_options(
+            Box::new(task),
+            Box::new(At(vec![Utc::now()])),
+            options.clone(),
+        );
+        for _ in 0..2 {
+            let task = BlockingTask {
+                gate: gate.clone(),
+                started: started.clone(),
+                finished: finished.clone(),
+            };
+            zeitschaltuhr.add_task_with_options(
+                Box::new(task),
+                Box::new(At(vec![Utc::now()])),
+                options.clone(),
+            );
+        }
+        let handle = zeitschaltuhr.run();
+
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::ExecutionStarted { .. })
+        })
+        .await;
+        let (result, _) = tokio::join!(handle.shutdown(std::time::Duration::from_secs(5)), async {
+            gate.open()
+        });
+
+        assert_eq!(Ok(()), result);
+        assert_eq!(1, started.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_tasks_of_exclusion_group_do_not_run_at_the_same_time() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let skipped = Arc::new(AtomicUsize::new(0));
+        let group = ExclusionGroup::new("database", ExclusionBehavior::Skip);
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_misfire_handler(|_| panic!("a busy group is no misfire"));
+        let recorded = skipped.clone();
+        zeitschaltuhr.add_listener(move |event: &Event| {
+            if let Event::OccurrenceSkipped {
+                reason: SkipReason::GroupBusy,
+                ..
+            } = event
+            {
+                recorded.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        for _ in 0..3 {
+            zeitschaltuhr.add_async_task_with_options(
+                Box::new(HangingTask(started.clone())),
+                Box::new(At(vec![Utc::now()])),
+                TaskOptions::default().with_exclusion_group(group.clone()),
+            );
+        }
+        let _handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        assert_eq!(1, started.load(Ordering::SeqCst));
+        assert_eq!(2, skipped.load(Ordering::SeqCst));
+    }
+
+    /// Records the highest number of executions running at the same time.
+    struct ConcurrencyTask {
+        gate: Gate,
+        running: Arc<AtomicUsize>,
+        max_running: Arc<AtomicUsize>,
+    }
+
+    impl Task for ConcurrencyTask {
+        fn execute(&self) -> TaskResult {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            self.gate.pass();
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn that_overdue_task_keeps_its_exclusion_group_until_it_returns() {
+        // timeouts of sync tasks need the real time, as the overdue task blocks a thread
+        let gate = Gate::default();
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let options = TaskOptions::default()
+            .with_timeout(std::time::Duration::from_millis(10))
+            .with_exclusion_group(ExclusionGroup::new("database", ExclusionBehavior::Wait));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_error_handler(|_| {});
+        let mut events = record_events(&mut zeitschaltuhr);
+        for _ in 0..2 {
+            let task = ConcurrencyTask {
+                gate: gate.clone(),
+                running: running.clone(),
+                max_running: max_running.clone(),
+            };
+            zeitschaltuhr.add_task_with_options(
+                Box::new(task),
+                Box::new(At(vec![Utc::now()])),
+                options.clone(),
+            );
+        }
+        let handle = zeitschaltuhr.run();
+
+        wait_for_event(&mut events, |event| {
+            matches!(event, Event::ExecutionFailed { .. })
+        })
+        .await;
+        settle().await;
+        gate.open();
+        handle
+            .shutdown(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert_eq!(1, max_running.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_occurrences_are_shifted_by_jitter() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let clock = mock_clock();
+        let options = TaskOptions::default().with_jitter(Jitter::hashed(Duration::hours(1), "a"));
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        zeitschaltuhr.add_task_with_options(
+            Box::new(CountingTask(counter.clone())),
+            Box::new(At(vec![clock.now()])),
+            options,
+        );
+        let _handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        assert_eq!(0, counter.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_executions_are_recorded_in_history() {
+        let clock = mock_clock();
+        let scheduled_at = clock.now();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock);
+        zeitschaltuhr.set_error_handler(|_| {});
+        let succeeding = zeitschaltuhr.add_task(
+            Box::new(PrintingTask::new("a".to_string())),
+            Box::new(At(vec![scheduled_at])),
+        );
+        let failing =
+            zeitschaltuhr.add_task(Box::new(FailingTask), Box::new(At(vec![scheduled_at])));
+        let handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        let history = handle.history(succeeding).unwrap();
+        assert_eq!(1, history.len());
+        assert_eq!(scheduled_at, history[0].scheduled_at);
+        assert!(history[0].started_at >= scheduled_at);
+        assert!(history[0].finished_at >= history[0].started_at);
+        assert_eq!(ExecutionOutcome::Succeeded, history[0].outcome);
+
+        let history = handle.history(failing).unwrap();
+        assert_eq!(
+            ExecutionOutcome::Failed("something went wrong".to_string()),
+            history[0].outcome
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_listeners_are_notified_about_lifecycle_events() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let clock = mock_clock();
+        let scheduled_at = clock.now();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock);
+        let recorded = events.clone();
+        zeitschaltuhr.add_listener(move |event: &Event| {
+            recorded.lock().unwrap().push(event.clone());
+        });
+        let id = zeitschaltuhr.add_task(
+            Box::new(PrintingTask::new("a".to_string())),
+            Box::new(At(vec![scheduled_at])),
+        );
+        let _handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        let events = events.lock().unwrap();
+        assert_eq!(5, events.len());
+        assert!(events.iter().all(|event| event.task_id() == id));
+        assert!(matches!(events[0], Event::TaskAdded { name: None, .. }));
+        assert!(matches!(
+            events[1],
+            Event::TriggerFired { scheduled_at: time, .. } if time == scheduled_at
+        ));
+        assert!(matches!(
+            events[2],
+            Event::ExecutionStarted { attempt: 1, .. }
+        ));
+        assert!(matches!(events[3], Event::ExecutionFinished { .. }));
+        assert!(matches!(events[4], Event::ScheduleExhausted { .. }));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_listeners_are_notified_about_added_and_removed_tasks() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let clock = mock_clock();
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock.clone());
+        let recorded = events.clone();
+        zeitschaltuhr.add_listener(move |event: &Event| {
+            recorded.lock().unwrap().push(event.clone());
+        });
+        let handle = zeitschaltuhr.run();
+
+        let id = handle
+            .add_task_with_options(
+                Box::new(PrintingTask::new("a".to_string())),
+                every_second_of(&clock),
+                TaskOptions::default().with_name("backup"),
+            )
+            .unwrap();
+        handle.remove_task(id).unwrap();
+        settle().await;
+
+        let events = events.lock().unwrap();
+        assert_eq!(2, events.len());
+        assert!(matches!(
+            &events[0],
+            Event::TaskAdded { task_id, name: Some(name), .. } if *task_id == id && name == "backup"
+        ));
+        assert!(matches!(events[1], Event::TaskRemoved { task_id, .. } if task_id == id));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn that_listeners_are_notified_about_failed_and_skipped_occurrences() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let clock = mock_clock();
+        let now = clock.now();
+        let options = TaskOptions::default().with_misfire_policy(MisfirePolicy::FireOnce);
+        let mut zeitschaltuhr = Zeitschaltuhr::default();
+        zeitschaltuhr.set_clock(clock);
+        zeitschaltuhr.set_error_handler(|_| {});
+        zeitschaltuhr.set_misfire_handler(|_| {});
+        let recorded = events.clone();
+        zeitschaltuhr.add_listener(move |event: &Event| {
+            recorded.lock().unwrap().push(event.clone());
+        });
+        zeitschaltuhr.add_task_with_options(
+            Box::new(FailingTask),
+            Box::new(At(vec![now - Duration::minutes(1), now])),
+            options,
+        );
+        let _handle = zeitschaltuhr.run();
+
+        settle().await;
+
+        let events = events.lock().unwrap();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            Event::OccurrenceSkipped {
+                reason: SkipReason::Misfire(MisfireReason::Missed),
+                ..
+            }
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            Event::ExecutionFailed { error, .. } if error == "something went wrong"
+        )));
+    }
 }